@@ -0,0 +1,55 @@
+use syn::{Data, DeriveInput, Fields, Ident, LitInt, Result};
+use syn::parse::{Parse, ParseStream};
+
+pub struct BitEnumInput {
+    ident: Ident,
+    len: LitInt,
+    variants: Vec<Ident>,
+}
+
+impl BitEnumInput {
+    pub fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    pub fn len(&self) -> &LitInt {
+        &self.len
+    }
+
+    pub fn variants(&self) -> &Vec<Ident> {
+        &self.variants
+    }
+}
+
+impl Parse for BitEnumInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let input = input.parse::<DeriveInput>()?;
+
+        let Data::Enum(data) = &input.data else {
+            return Err(syn::Error::new(input.ident.span(), "`BitEnum` can only be derived for enums"));
+        };
+
+        if !input.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(&input.generics, "`BitEnum` cannot be derived for generic enums"));
+        }
+
+        let Some(attr) = input.attrs.iter().find(|attr| attr.path().is_ident("bits")) else {
+            let err = "expected `#[bits(N)]` attribute that specifies the size of the enum";
+            return Err(syn::Error::new(input.ident.span(), err));
+        };
+        let len = attr.parse_args::<LitInt>()?;
+
+        let variants = data.variants.iter()
+            .map(|variant| match variant.fields {
+                Fields::Unit => Ok(variant.ident.clone()),
+                _ => Err(syn::Error::new_spanned(&variant.fields, "`BitEnum` variants cannot have fields")),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(BitEnumInput {
+            ident: input.ident,
+            len,
+            variants,
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use syn::{Result, Token, Type};
+use syn::parse::{Parse, ParseStream};
+
+use crate::bit_seq_input::BitSeqInput;
+
+pub struct BitSeqAsInput {
+    /// The type of the sequence, like `RegWord` in `bseq_as!(RegWord; 0110 rd:5)`.
+    ty: Type,
+    seq: BitSeqInput,
+}
+
+impl BitSeqAsInput {
+    pub fn ty(&self) -> &Type {
+        &self.ty
+    }
+
+    pub fn seq(&self) -> &BitSeqInput {
+        &self.seq
+    }
+}
+
+impl Parse for BitSeqAsInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ty = input.parse::<Type>().map_err(|err| {
+            let msg = "expected the type of the bit sequence followed by `;`, like `bseq_as!(u32; 0110)`";
+            syn::Error::new(err.span(), msg)
+        })?;
+        input.parse::<Token![;]>()?;
+        let seq = input.parse::<BitSeqInput>()?;
+
+        Ok(BitSeqAsInput {
+            ty,
+            seq,
+        })
+    }
+}
@@ -1,61 +1,261 @@
-use syn::{Expr, ExprLit, ExprPath, LitInt, Result, Token};
-use syn::parse::{Parse, ParseStream, Peek};
+use proc_macro2::Span;
+use syn::{Expr, ExprLit, LitInt, Result, Token};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
 
 pub struct BitSeqInput {
     bit_segments: Vec<BitSegment>,
+    /// The exact number of bits the segments must take up, declared by `@N;`.
+    width: Option<LitInt>,
+    /// Whether values must fit into their width instead of being truncated, declared by `@strict;`.
+    strict: bool,
+    /// Whether the sequence is shifted up against the most significant bit of its type, as in `bseq_32_left!`.
+    left_justified: bool,
+    /// Whether the first segment is the least significant one, declared by `@lsb;`.
+    lsb_first: bool,
 }
 
+#[derive(Clone)]
 pub enum BitSegment {
-    Bits(syn::LitInt),
-    Expr(syn::Expr, syn::LitInt),
+    Bits(BitString),
+    Expr(syn::Expr, SegmentLen),
+    /// A variant of an enum deriving `BitEnum`, whose width is given by the enum.
+    Enum(syn::ExprPath),
+    /// A run of `0` or `1` bits that takes up the width left in a sized macro, like `..0`.
+    Fill(syn::LitInt),
+    /// Segments repeated a constant number of times, like `[01 x:2]{N}`, with the braced count.
+    Repeat(Vec<BitSegment>, syn::Block),
+}
+
+/// The width of a length expression.
+#[derive(Clone)]
+pub enum SegmentLen {
+    /// An integer literal, like `x:3`.
+    Lit(syn::LitInt),
+    /// The width of a signed value, like `imm:s12`, which is sign-extended if it is the most significant segment.
+    Signed(syn::LitInt),
+    /// A braced constant expression of type `usize`, like `x:{N * 2}`.
+    Const(syn::Block),
+    /// A parenthesized expression of type `usize` evaluated at runtime, like `x:(len)`, without its parentheses.
+    Runtime(Box<syn::Expr>),
+}
+
+/// The number of repetitions of a segment or group, like `{4}`.
+enum Repetition {
+    Count(usize),
+    /// A braced constant expression of type `usize`, like `{N}`.
+    Const(syn::Block),
+}
+
+/// A raw bit sequence, where `x` marks a don't-care bit.
+#[derive(Clone)]
+pub struct BitString {
+    digits: String,
+    /// The integer type suffix, like `u8` in `0101u8`.
+    suffix: Option<syn::Ident>,
+    span: Span,
+}
+
+impl BitString {
+    pub fn new(digits: String, span: Span) -> Self {
+        BitString { digits, suffix: None, span }
+    }
+
+    pub fn with_suffix(self, suffix: Option<syn::Ident>) -> Self {
+        BitString { suffix, ..self }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn suffix(&self) -> Option<&syn::Ident> {
+        self.suffix.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.digits.len()
+    }
+
+    /// The value of the bits, with don't-care bits set to `0`.
+    pub fn value(&self) -> u128 {
+        let bits = self.digits.replace('x', "0");
+        u128::from_str_radix(&bits, 2).unwrap()
+    }
+
+    /// The mask of all bits that are not don't-care bits.
+    pub fn care_mask(&self) -> u128 {
+        let bits: String = self.digits.chars().map(|c| if c == 'x' { '0' } else { '1' }).collect();
+        u128::from_str_radix(&bits, 2).unwrap()
+    }
+
+    pub fn has_dont_care(&self) -> bool {
+        self.digits.contains('x')
+    }
+
+    /// Returns the same bits with all don't-care bits set to `0`.
+    pub fn without_dont_care(&self) -> BitString {
+        BitString { digits: self.digits.replace('x', "0"), ..self.clone() }
+    }
+}
+
+impl BitSegment {
+    pub fn span(&self) -> Span {
+        match self {
+            BitSegment::Bits(bits) => bits.span(),
+            BitSegment::Expr(expr, _) => expr.span(),
+            BitSegment::Enum(path) => path.span(),
+            BitSegment::Fill(bit) => bit.span(),
+            BitSegment::Repeat(_, count) => count.span(),
+        }
+    }
 }
 
 impl BitSeqInput {
+    pub fn new(bit_segments: Vec<BitSegment>) -> Self {
+        BitSeqInput { bit_segments, width: None, strict: false, left_justified: false, lsb_first: false }
+    }
+
+    /// Creates a bit sequence with the same flags but other segments.
+    pub fn with_segments(&self, bit_segments: Vec<BitSegment>) -> Self {
+        BitSeqInput {
+            bit_segments,
+            width: self.width.clone(),
+            strict: self.strict,
+            left_justified: self.left_justified,
+            lsb_first: self.lsb_first,
+        }
+    }
+
     pub fn segments(&self) -> &Vec<BitSegment> {
         &self.bit_segments
     }
 
-    fn parse_length_definition(input: &ParseStream) -> Result<syn::LitInt> {
+    pub fn width(&self) -> Option<&LitInt> {
+        self.width.as_ref()
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_strict(&mut self) {
+        self.strict = true;
+    }
+
+    pub fn is_left_justified(&self) -> bool {
+        self.left_justified
+    }
+
+    pub fn set_left_justified(&mut self) {
+        self.left_justified = true;
+    }
+
+    pub fn is_lsb_first(&self) -> bool {
+        self.lsb_first
+    }
+
+    // parse flags in front of the segments, like `@32;`, `@strict;` or `@lsb;`
+    fn parse_flags(&mut self, input: ParseStream) -> Result<()> {
+        while input.peek(Token![@]) {
+            input.parse::<Token![@]>()?;
+
+            if input.peek(syn::LitInt) {
+                let width = input.parse::<LitInt>()?;
+                if self.width.is_some() {
+                    return Err(syn::Error::new(width.span(), "the width of the bit sequence is already declared"));
+                }
+                self.width = Some(width);
+            } else if input.peek(syn::Ident) {
+                let flag = input.parse::<syn::Ident>()?;
+                match flag.to_string().as_str() {
+                    "strict" => self.strict = true,
+                    "lsb" => self.lsb_first = true,
+                    _ => return Err(syn::Error::new(flag.span(), "unknown flag, expected width, `strict` or `lsb`")),
+                }
+            } else {
+                return Err(input.error("expected width of the bit sequence or flag"));
+            }
+
+            if !input.peek(Token![;]) {
+                return Err(input.error("expected `;` after flag"));
+            }
+            input.parse::<Token![;]>()?;
+        }
+        Ok(())
+    }
+
+    fn parse_length_definition(input: &ParseStream) -> Result<SegmentLen> {
         if !input.peek(Token![:]) {
             return Err(input.error("expected `:`"));
         }
 
+        // a path written with single colons, such as `isa:OP_LOAD:7`
+        if input.peek2(syn::Ident) && input.peek3(Token![:]) {
+            let colon = input.parse::<Token![:]>()?;
+            let err = "expected `::` to separate path segments, a single `:` separates the value from its width";
+            return Err(syn::Error::new(colon.span, err));
+        }
+
         input.parse::<Token![:]>()?;
 
+        if input.peek(syn::token::Brace) {
+            return Ok(SegmentLen::Const(input.parse::<syn::Block>()?));
+        }
+
+        if input.peek(syn::token::Paren) {
+            return Ok(SegmentLen::Runtime(input.parse::<syn::ExprParen>()?.expr));
+        }
+
+        // a signed width, like `s12`, is tokenized as identifier
+        if let Some((ident, len)) = peek_signed_width(input) {
+            input.parse::<syn::Ident>()?;
+            if len.base10_parse::<usize>()? == 0 {
+                return Err(syn::Error::new(ident.span(), "the width of a signed value must not be 0"));
+            }
+            return Ok(SegmentLen::Signed(len));
+        }
+
         if !input.peek(syn::LitInt) {
-            return Err(input.error("expected integer that specifies size of bit sequence"));
+            let err = "expected integer, signed width like `s12`, braced constant expression or parenthesized \
+                expression that specifies size of bit sequence";
+            return Err(input.error(err));
         }
 
-        input.parse::<syn::LitInt>()
+        Ok(SegmentLen::Lit(input.parse::<syn::LitInt>()?))
     }
 
-    // parse unary operator to bit segment expression
-    fn parse_unary(input: &ParseStream) -> Result<BitSegment> {
-        let expr = input.parse::<Expr>()?;
-        let size = BitSeqInput::parse_length_definition(&input)?;
+    // parse length expression or enum variant to bit segment
+    fn parse_expr(input: &ParseStream) -> Result<BitSegment> {
+        if let Some(separator) = find_path_width_separator(input) {
+            let err = "expected `:` to separate the path from its width, `::` separates path segments";
+            return Err(syn::Error::new_spanned(separator, err));
+        }
+
+        let expr = parse_segment_expr(input)?;
+
+        if !input.peek(Token![:]) {
+            // a path without length is an enum variant
+            if let Expr::Path(path) = &expr {
+                if path.path.segments.len() > 1 {
+                    return Ok(BitSegment::Enum(path.clone()));
+                }
+            }
+            return Err(input.error("expected `:`"));
+        }
+
+        check_segment_expr(&expr)?;
+        let size = BitSeqInput::parse_length_definition(input)?;
         Ok(BitSegment::Expr(expr, size))
     }
 
-    // parse ident and num expression to bit segement
-    fn parse_expr(input: &ParseStream) -> Result<BitSegment> {
-        let val = if input.peek(syn::Ident) {
-            let ident = input.parse::<syn::Ident>()?;
-            syn::Expr::Path(ExprPath {
-                attrs: vec![],
-                qself: None,
-                path: ident.into(),
-            })
-        } else {
-            let num = input.parse::<syn::LitInt>()?;
-            syn::Expr::Lit(
-                ExprLit {
-                    attrs: vec![],
-                    lit: syn::Lit::Int(num),
-                })
-        };
-
-        let size = BitSeqInput::parse_length_definition(&input)?;
-        Ok(BitSegment::Expr(val, size))
+    // parse don't-care bits starting with `x`, which are tokenized as identifier
+    fn parse_dont_care(input: &ParseStream) -> Result<BitSegment> {
+        let ident = input.parse::<syn::Ident>()?;
+        let digits = ident.to_string();
+        check_bits_len(digits.len(), ident.span())?;
+        Ok(BitSegment::Bits(BitString::new(digits, ident.span())))
     }
 
     // parse raw bits
@@ -67,9 +267,16 @@ impl BitSeqInput {
             return Err(syn::Error::new(num.span(), "negative numbers are not allowed"));
         }
 
-        // check for hexadecimal literal
-        if num_string.starts_with("0x") {
-            let bit_len = (num_string.len() - 2) * 4;
+        // check for hexadecimal and octal literals, whose width includes neither separators nor the type suffix
+        let radix = [("0x", "hex", 4), ("0o", "octal", 3)].into_iter()
+            .find(|(prefix, _, _)| num_string.starts_with(prefix));
+        if let Some((prefix, name, digit_len)) = radix {
+            let digits = &num_string[prefix.len()..num_string.len() - num.suffix().len()];
+            let bit_len = digits.chars().filter(|c| *c != '_').count() * digit_len;
+            if bit_len > 128 {
+                let err = format!("the {} value takes up {} bits, but at most 128 bits are supported", name, bit_len);
+                return Err(syn::Error::new(num.span(), err));
+            }
             let lit_len = LitInt::new(&bit_len.to_string(), num.span());
 
             let expr = syn::Expr::Lit(
@@ -78,55 +285,396 @@ impl BitSeqInput {
                     lit: syn::Lit::Int(num),
                 });
 
-            return Ok(BitSegment::Expr(expr, lit_len));
+            return Ok(BitSegment::Expr(expr, SegmentLen::Lit(lit_len)));
         }
 
-        // check for binary literal
-        let is_binary = num_string.chars().all(|c| c == '0' || c == '1');
+        // check for binary literal with an optional `0b` prefix,
+        // don't-care bits are tokenized as suffix, possibly followed by a type suffix
+        let bits = num_string.strip_prefix("0b").unwrap_or(&num_string);
+        let (digits, suffix) = split_type_suffix(bits);
+        let digits = digits.replace('_', "");
+        let is_binary = digits.chars().all(|c| c == '0' || c == '1' || c == 'x');
         if !is_binary {
-            let err = format!("expected bit sequence but got integer instead.");
+            let err = "expected bit sequence but got integer instead.";
             return Err(syn::Error::new(num.span(), err));
         }
-        Ok(BitSegment::Bits(num))
+        check_bits_len(digits.len(), num.span())?;
+
+        let suffix = suffix.map(|suffix| syn::Ident::new(suffix, num.span()));
+        Ok(BitSegment::Bits(BitString::new(digits, num.span()).with_suffix(suffix)))
     }
 }
 
-impl Parse for BitSeqInput {
-    fn parse(input: ParseStream) -> Result<Self> {
+impl BitSeqInput {
+    // parse sized literal in Verilog notation, like `8'hFF`, which is tokenized as integer and lifetime
+    fn parse_sized_literal(input: &ParseStream) -> Result<BitSegment> {
+        let width_lit = input.parse::<syn::LitInt>()?;
+        let value = input.parse::<syn::Lifetime>()?;
+        let span = value.span();
+
+        let width: usize = width_lit.base10_parse()?;
+        if width == 0 {
+            return Err(syn::Error::new(width_lit.span(), "the width of a sized literal must not be 0"));
+        }
+        check_bits_len(width, width_lit.span())?;
+
+        let value_string = value.ident.to_string();
+        let (radix, digits) = value_string.split_at(value_string.chars().next().map_or(0, char::len_utf8));
+        let digits = digits.replace('_', "").to_lowercase();
+        if digits.is_empty() {
+            return Err(syn::Error::new(span, "expected value after the radix of the sized literal"));
+        }
+
+        let bits = match radix {
+            "b" | "B" => digits_to_bits(&digits, 1, span)?,
+            "o" | "O" => digits_to_bits(&digits, 3, span)?,
+            "h" | "H" => digits_to_bits(&digits, 4, span)?,
+            "d" | "D" => {
+                let value = digits.parse::<u128>()
+                    .map_err(|_| syn::Error::new(span, "expected decimal value of at most 128 bits"))?;
+                format!("{:b}", value)
+            }
+            _ => return Err(syn::Error::new(span, "expected radix `b`, `o`, `d` or `h` of the sized literal")),
+        };
+
+        // like in Verilog, a value starting with don't-care bits is extended by don't-care bits
+        let significant = bits.trim_start_matches('0');
+        if significant.len() > width {
+            let err = format!("the value takes up {} bits, but its width is only {} bits", significant.len(), width);
+            return Err(syn::Error::new(span, err));
+        }
+        let fill = if significant.starts_with('x') { "x" } else { "0" };
+        let bits = fill.repeat(width - significant.len()) + significant;
+
+        Ok(BitSegment::Bits(BitString::new(bits, width_lit.span())))
+    }
+
+    // parse fill segment, like `..0`
+    fn parse_fill(input: &ParseStream) -> Result<BitSegment> {
+        input.parse::<Token![..]>()?;
+        let bit = match input.parse::<syn::LitInt>() {
+            Ok(bit) if bit.to_string() == "0" || bit.to_string() == "1" => bit,
+            _ => return Err(input.error("expected `0` or `1` after `..`")),
+        };
+        Ok(BitSegment::Fill(bit))
+    }
+
+    // parse segments in brackets, like `[01 x:2]`
+    fn parse_group(input: &ParseStream) -> Result<Vec<BitSegment>> {
+        let content;
+        syn::bracketed!(content in input);
+        Ok(BitSeqInput::parse_until(&content, |_| false)?.bit_segments)
+    }
+
+    // parse the number of repetitions of the preceding segment or group, like `{4}` or `{N}`
+    fn parse_repetition(input: &ParseStream) -> Result<Option<Repetition>> {
+        if !input.peek(syn::token::Brace) {
+            return Ok(None);
+        }
+
+        let block = input.parse::<syn::Block>()?;
+        let [syn::Stmt::Expr(Expr::Lit(ExprLit { lit: syn::Lit::Int(count), .. }), None)] = &block.stmts[..]
+        else {
+            return Ok(Some(Repetition::Const(block)));
+        };
+
+        // the segments are repeated when parsing, so an excessive count is rejected before repeating them
+        let count: usize = count.base10_parse()?;
+        if count > 128 {
+            let err = format!("the segments are repeated {} times, but at most 128 bits are supported", count);
+            return Err(syn::Error::new_spanned(block, err));
+        }
+        Ok(Some(Repetition::Count(count)))
+    }
+
+    // repeat segments a constant number of times, like `[01 x:2]{N}`, where a single bit like `1{N}` is the same
+    // as the bits `!0:{N}`
+    fn repeat_const(group: Vec<BitSegment>, count: syn::Block) -> Result<BitSegment> {
+        let span = count.span();
+        if let [BitSegment::Bits(bits)] = &group[..] {
+            let value: Option<Expr> = match bits.digits.as_str() {
+                "1" if bits.suffix().is_none() => Some(syn::parse_quote_spanned!(span=> !0)),
+                "0" if bits.suffix().is_none() => Some(syn::parse_quote_spanned!(span=> 0)),
+                _ => None,
+            };
+            if let Some(value) = value {
+                return Ok(BitSegment::Expr(value, SegmentLen::Const(count)));
+            }
+        }
+
+        // the width of the repeated segments must be known to the compiler
+        for seg in &group {
+            let err = match seg {
+                BitSegment::Fill(_) => "fill segments cannot be repeated a constant number of times",
+                BitSegment::Expr(_, SegmentLen::Runtime(_)) => {
+                    "lengths that are only known at runtime cannot be repeated a constant number of times"
+                }
+                BitSegment::Bits(bits) if bits.has_dont_care() => {
+                    "don't-care bits cannot be repeated a constant number of times"
+                }
+                _ => continue,
+            };
+            return Err(syn::Error::new(seg.span(), err));
+        }
+        Ok(BitSegment::Repeat(group, count))
+    }
+
+    /// Parses bit segments until the input is exhausted or `stop` returns true.
+    ///
+    /// This is used by macros that embed bit sequences into a larger syntax,
+    /// like the arms of `bseq_match!`.
+    pub fn parse_until(input: ParseStream, stop: fn(ParseStream) -> bool) -> Result<Self> {
         let mut bit_segments = Vec::new();
 
-        while !input.is_empty() {
-            if peek_expr_with_token(|expr| matches!(expr, Expr::Unary(_)), Token![:], input) {
-                let segment = BitSeqInput::parse_unary(&input)?;
-                bit_segments.push(segment);
-            } else if input.peek(syn::Ident)
-                || (input.peek(syn::LitInt) && input.peek2(Token![:]))
-            {
-                let segment = BitSeqInput::parse_expr(&input)?;
-                bit_segments.push(segment);
-            } else if input.peek(syn::LitInt) {
-                let segment = BitSeqInput::parse_bits(&input)?;
-                bit_segments.push(segment);
+        while !input.is_empty() && !stop(input) {
+            if input.peek(syn::token::Bracket) && !peek_length_expr(input) {
+                let group = BitSeqInput::parse_group(&input)?;
+                let count = match BitSeqInput::parse_repetition(&input)? {
+                    None => 1,
+                    Some(Repetition::Count(count)) => count,
+                    Some(Repetition::Const(count)) => {
+                        bit_segments.push(BitSeqInput::repeat_const(group, count)?);
+                        continue;
+                    }
+                };
+                for _ in 0..count {
+                    bit_segments.extend(group.iter().cloned());
+                }
+                continue;
             }
-            // parse an expression segment
-            else {
+
+            let segment = if peek_length_expr(input) {
+                BitSeqInput::parse_expr(&input)?
+            } else if input.peek(Token![..]) {
+                BitSeqInput::parse_fill(&input)?
+            } else if input.peek(syn::LitInt) && input.peek2(syn::Lifetime) {
+                BitSeqInput::parse_sized_literal(&input)?
+            } else if input.peek(syn::LitInt) {
+                BitSeqInput::parse_bits(&input)?
+            } else if peek_dont_care(input) {
+                BitSeqInput::parse_dont_care(&input)?
+            } else if peek_expr_start(input) {
+                BitSeqInput::parse_expr(&input)?
+            } else {
                 return Err(input.error("expected bit sequence, hex or length defined expression"));
+            };
+            match BitSeqInput::parse_repetition(&input)? {
+                None => bit_segments.push(segment),
+                Some(Repetition::Count(count)) => bit_segments.extend(std::iter::repeat(segment).take(count)),
+                Some(Repetition::Const(count)) => {
+                    bit_segments.push(BitSeqInput::repeat_const(vec![segment], count)?);
+                }
             }
         }
 
-        Ok(BitSeqInput {
-            bit_segments,
-        })
+        Ok(BitSeqInput::new(bit_segments))
+    }
+
+    /// Parses flags like `@32;`, followed by bit segments until the input is exhausted or `stop` returns true.
+    pub fn parse_flagged_until(input: ParseStream, stop: fn(ParseStream) -> bool) -> Result<Self> {
+        let mut flags = BitSeqInput::new(Vec::new());
+        flags.parse_flags(input)?;
+        flags.parse_segments_until(input, stop)
+    }
+
+    /// Parses bit segments with the same flags, like the end of a range in `bseq_lit!`.
+    pub fn parse_segments_until(&self, input: ParseStream, stop: fn(ParseStream) -> bool) -> Result<Self> {
+        let mut seq = BitSeqInput::parse_until(input, stop)?;
+        // the segments are kept in layout order, so the first segment is the most significant one
+        if self.lsb_first {
+            reverse_segments(&mut seq.bit_segments);
+        }
+        Ok(self.with_segments(seq.bit_segments))
+    }
+}
+
+impl Parse for BitSeqInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        BitSeqInput::parse_flagged_until(input, |_| false)
     }
 }
 
 // Helper
-fn peek_expr_with_token<T: Peek>(check: fn(Expr) -> bool, token: T, input: ParseStream) -> bool {
+fn peek_length_expr(input: ParseStream) -> bool {
     let forked = input.fork();
-    let expr_check = match forked.parse::<Expr>() {
-        Ok(expr) => check(expr),
+    parse_segment_expr(&forked).is_ok() && forked.peek(Token![:])
+}
+
+/// Peeks a signed width like `s12`, returning the identifier and its width.
+fn peek_signed_width(input: &ParseStream) -> Option<(syn::Ident, LitInt)> {
+    let ident = input.fork().parse::<syn::Ident>().ok()?;
+    let len = ident.to_string().strip_prefix('s')?.to_string();
+    if len.is_empty() || !len.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let len = LitInt::new(&len, ident.span());
+    Some((ident, len))
+}
+
+/// Finds a `::` that is directly followed by an integer in a leading path, as in `isa::OP_LOAD::7`.
+fn find_path_width_separator(input: ParseStream) -> Option<Token![::]> {
+    let forked = input.fork();
+    if forked.peek(Token![::]) {
+        forked.parse::<Token![::]>().ok()?;
+    }
+
+    loop {
+        forked.call(syn::Ident::parse_any).ok()?;
+        let separator = forked.parse::<Token![::]>().ok()?;
+        if forked.peek(syn::LitInt) {
+            return Some(separator);
+        }
+    }
+}
+
+fn parse_segment_expr(input: ParseStream) -> Result<Expr> {
+    // integers are never called or indexed, so `1 (a):2` are two segments
+    if input.peek(syn::LitInt) && !input.peek(Token![-]) {
+        let num = input.parse::<syn::LitInt>()?;
+        return Ok(syn::Expr::Lit(
+            ExprLit {
+                attrs: vec![],
+                lit: syn::Lit::Int(num),
+            }));
+    }
+
+    // a brace after the expression is a repetition, as in `Mode::Fast{2}`
+    let forked = input.fork();
+    let expr = Expr::parse_without_eager_brace(&forked);
+
+    // a range is no segment, so `Mode::Fast ..0` is a variant followed by a fill segment
+    if let Ok(Expr::Range(syn::ExprRange { start: Some(_), .. })) = expr {
+        let start = input.step(|cursor| {
+            let mut tokens = proc_macro2::TokenStream::new();
+            let mut rest = *cursor;
+            while let Some((tt, next)) = rest.token_tree() {
+                if is_range_dots(rest) {
+                    break;
+                }
+                tokens.extend(std::iter::once(tt));
+                rest = next;
+            }
+            Ok((tokens, rest))
+        })?;
+        return syn::parse2(start);
+    }
+
+    // an enum variant has no width, so `Mode::Fast -a:3`, `Mode::Fast !a:3` and `Mode::Fast (a):3` are a variant
+    // followed by another segment rather than a subtraction, a macro call or a call
+    let is_length_expr = matches!(&expr, Ok(expr) if forked.peek(Token![:])
+        && check_segment_expr(expr).is_ok()
+        && !is_variant_call(expr));
+    if !is_length_expr {
+        let forked = input.fork();
+        if let Ok(path) = forked.parse::<syn::ExprPath>() {
+            if path.qself.is_none() && path.path.segments.len() > 1 {
+                return Ok(Expr::Path(input.parse()?));
+            }
+        }
+    }
+
+    Expr::parse_without_eager_brace(input)
+}
+
+/// Returns whether the expression calls a path ending in a capitalized name, like `Mode::Fast(a)`.
+///
+/// Variants of bit enums have no fields and functions are not capitalized, so this is a variant followed by
+/// a parenthesized segment.
+fn is_variant_call(expr: &Expr) -> bool {
+    let Expr::Call(call) = expr else { return false };
+    let Expr::Path(path) = &*call.func else { return false };
+    path.path.segments.len() > 1
+        && path.path.segments.last().is_some_and(|seg| seg.ident.to_string().starts_with(char::is_uppercase))
+}
+
+fn is_range_dots(cursor: syn::buffer::Cursor) -> bool {
+    match cursor.punct() {
+        Some((first, next)) if first.as_char() == '.' && first.spacing() == proc_macro2::Spacing::Joint => {
+            next.punct().is_some_and(|(second, _)| second.as_char() == '.')
+        }
+        _ => false,
+    }
+}
+
+fn peek_expr_start(input: ParseStream) -> bool {
+    input.peek(syn::Ident::peek_any)
+        || input.peek(syn::Lit)
+        || input.peek(syn::token::Paren)
+        || input.peek(syn::token::Bracket)
+        || input.peek(Token![!])
+        || input.peek(Token![-])
+        || input.peek(Token![*])
+        || input.peek(Token![&])
+        || input.peek(Token![::])
+        || input.peek(Token![<])
+}
+
+/// Checks that the expression of a length expression can be unambiguously followed by `:`.
+fn check_segment_expr(expr: &Expr) -> Result<()> {
+    match expr {
+        Expr::Lit(ExprLit { lit: syn::Lit::Int(_), .. })
+        | Expr::Path(_)
+        | Expr::Paren(_)
+        | Expr::Field(_)
+        | Expr::MethodCall(_)
+        | Expr::Call(_)
+        | Expr::Index(_)
+        | Expr::Cast(_)
+        | Expr::Unary(_) => Ok(()),
+        _ => Err(syn::Error::new_spanned(expr, "expected variable, integer, path, field access, \
+            method call, index, cast or unary expression, other expressions must be parenthesized")),
+    }
+}
+
+/// Splits an integer type suffix, such as `u8`, from the end of a literal.
+fn split_type_suffix(lit: &str) -> (&str, Option<&str>) {
+    const SUFFIXES: [&str; 12] = ["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"];
+    SUFFIXES.iter()
+        .find(|suffix| lit.ends_with(*suffix))
+        .map_or((lit, None), |suffix| (&lit[..lit.len() - suffix.len()], Some(suffix)))
+}
+
+/// Converts the digits of a sized literal to bits, with `digit_len` bits per digit and `x` for don't-care bits.
+fn digits_to_bits(digits: &str, digit_len: usize, span: Span) -> Result<String> {
+    digits.chars()
+        .map(|c| match c {
+            'x' => Ok("x".repeat(digit_len)),
+            _ => c.to_digit(1 << digit_len)
+                .map(|digit| format!("{:0width$b}", digit, width = digit_len))
+                .ok_or_else(|| syn::Error::new(span, format!("invalid digit `{}` in sized literal", c))),
+        })
+        .collect()
+}
+
+/// Reverses the order of the segments, including the segments of repeated groups, which switches between the
+/// written order of `@lsb;` and the layout order.
+pub fn reverse_segments(segments: &mut [BitSegment]) {
+    segments.reverse();
+    for seg in segments {
+        if let BitSegment::Repeat(group, _) = seg {
+            reverse_segments(group);
+        }
+    }
+}
+
+fn check_bits_len(len: usize, span: Span) -> Result<()> {
+    if len > 128 {
+        let err = format!("the bit sequence takes up {} bits, but at most 128 bits are supported", len);
+        return Err(syn::Error::new(span, err));
+    }
+    Ok(())
+}
+
+fn peek_dont_care(input: ParseStream) -> bool {
+    let forked = input.fork();
+    let is_bits = match forked.parse::<syn::Ident>() {
+        Ok(ident) => {
+            let ident = ident.to_string();
+            ident.starts_with('x') && ident.chars().all(|c| c == '0' || c == '1' || c == 'x')
+        }
         Err(_) => false,
     };
 
-    expr_check && forked.peek(token)
-}
\ No newline at end of file
+    // `x:3` is a length expression with a variable `x`
+    is_bits && !forked.peek(Token![:])
+}
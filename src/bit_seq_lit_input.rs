@@ -0,0 +1,42 @@
+use syn::{Result, Token};
+use syn::parse::{Parse, ParseStream};
+
+use crate::bit_seq_input::BitSeqInput;
+
+pub struct BitSeqLitInput {
+    start: BitSeqInput,
+    /// The end of an inclusive range pattern, like `0111` in `0000 ..= 0111`.
+    end: Option<BitSeqInput>,
+}
+
+impl BitSeqLitInput {
+    pub fn start(&self) -> &BitSeqInput {
+        &self.start
+    }
+
+    pub fn end(&self) -> Option<&BitSeqInput> {
+        self.end.as_ref()
+    }
+}
+
+impl Parse for BitSeqLitInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let start = BitSeqInput::parse_flagged_until(input, |input| input.peek(Token![..=]))?;
+
+        let end = if input.peek(Token![..=]) {
+            input.parse::<Token![..=]>()?;
+            // the flags of the start apply to the end as well
+            if input.peek(Token![@]) {
+                return Err(input.error("flags are only allowed before the start of the range and apply to both ends"));
+            }
+            Some(start.parse_segments_until(input, |_| false)?)
+        } else {
+            None
+        };
+
+        Ok(BitSeqLitInput {
+            start,
+            end,
+        })
+    }
+}
@@ -0,0 +1,136 @@
+use syn::{Expr, Result, Token};
+use syn::parse::{Parse, ParseStream};
+
+use crate::bit_seq_input::BitSeqInput;
+
+pub struct BitSeqMatchInput {
+    value: Expr,
+    arms: Vec<BitSeqArm>,
+}
+
+pub struct BitSeqMatchesInput {
+    value: Expr,
+    pattern: BitSeqInput,
+    guard: Option<Expr>,
+}
+
+pub struct BitSeqArm {
+    /// The bit sequence pattern of the arm, `None` for the wildcard `_`.
+    pub pattern: Option<BitSeqInput>,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+impl BitSeqMatchInput {
+    pub fn value(&self) -> &Expr {
+        &self.value
+    }
+
+    pub fn arms(&self) -> &Vec<BitSeqArm> {
+        &self.arms
+    }
+
+    fn parse_arm(input: ParseStream) -> Result<BitSeqArm> {
+        let pattern = if input.peek(Token![_]) && (input.peek2(Token![=>]) || input.peek2(Token![if])) {
+            input.parse::<Token![_]>()?;
+            None
+        } else {
+            let pattern = BitSeqInput::parse_flagged_until(input, |input| {
+                input.peek(Token![=>]) || input.peek(Token![if]) || input.peek(Token![,])
+            })?;
+            Some(pattern)
+        };
+
+        let guard = if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            Some(input.parse::<Expr>()?)
+        } else {
+            None
+        };
+
+        if !input.peek(Token![=>]) {
+            return Err(input.error("expected `=>` after bit sequence pattern"));
+        }
+        input.parse::<Token![=>]>()?;
+
+        let body = input.parse::<Expr>()?;
+
+        // like in `match`, the comma is optional after block bodies and the last arm
+        let is_block = matches!(body, Expr::Block(_) | Expr::If(_) | Expr::Match(_) | Expr::Loop(_)
+            | Expr::While(_) | Expr::ForLoop(_) | Expr::Unsafe(_));
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        } else if !is_block && !input.is_empty() {
+            return Err(input.error("expected `,` following match arm"));
+        }
+
+        Ok(BitSeqArm { pattern, guard, body })
+    }
+}
+
+impl Parse for BitSeqMatchInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let value = Expr::parse_without_eager_brace(input)?;
+
+        let content;
+        syn::braced!(content in input);
+
+        let mut arms = Vec::new();
+        while !content.is_empty() {
+            arms.push(BitSeqMatchInput::parse_arm(&content)?);
+        }
+
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after match arms"));
+        }
+
+        Ok(BitSeqMatchInput {
+            value,
+            arms,
+        })
+    }
+}
+
+impl BitSeqMatchesInput {
+    pub fn value(&self) -> &Expr {
+        &self.value
+    }
+
+    pub fn pattern(&self) -> &BitSeqInput {
+        &self.pattern
+    }
+
+    pub fn guard(&self) -> &Option<Expr> {
+        &self.guard
+    }
+}
+
+impl Parse for BitSeqMatchesInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let value = input.parse::<Expr>()?;
+
+        if !input.peek(Token![,]) {
+            return Err(input.error("expected `,` followed by the bit sequence pattern"));
+        }
+        input.parse::<Token![,]>()?;
+
+        let pattern = BitSeqInput::parse_flagged_until(input, |input| input.peek(Token![if]))?;
+
+        let guard = if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            Some(input.parse::<Expr>()?)
+        } else {
+            None
+        };
+
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after bit sequence pattern"));
+        }
+
+        Ok(BitSeqMatchesInput {
+            value,
+            pattern,
+            guard,
+        })
+    }
+}
@@ -0,0 +1,78 @@
+use syn::{Fields, Ident, ItemStruct, LitInt, Result, Type, Visibility};
+use syn::parse::{Parse, ParseStream};
+
+pub struct BitSeqStructInput {
+    /// The struct with all `#[bits(N)]` attributes removed.
+    item: ItemStruct,
+    fields: Vec<BitField>,
+}
+
+pub struct BitField {
+    pub ident: Ident,
+    pub ty: Type,
+    pub vis: Visibility,
+    pub len: LitInt,
+}
+
+/// The arguments of the `#[bit_seq]` attribute, i.e. the optional backing type.
+pub struct BitSeqStructArgs {
+    pub backing: Option<Type>,
+}
+
+impl BitSeqStructInput {
+    pub fn item(&self) -> &ItemStruct {
+        &self.item
+    }
+
+    pub fn fields(&self) -> &Vec<BitField> {
+        &self.fields
+    }
+}
+
+impl Parse for BitSeqStructInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut item = input.parse::<ItemStruct>()?;
+
+        let Fields::Named(named) = &mut item.fields else {
+            return Err(syn::Error::new_spanned(&item.fields, "`#[bit_seq]` requires a struct with named fields"));
+        };
+
+        let mut fields = Vec::new();
+        for field in named.named.iter_mut() {
+            let ident = field.ident.clone().expect("named fields have identifiers");
+
+            let Some(pos) = field.attrs.iter().position(|attr| attr.path().is_ident("bits")) else {
+                let err = format!("expected `#[bits(N)]` attribute that specifies the size of `{}`", ident);
+                return Err(syn::Error::new(ident.span(), err));
+            };
+            let attr = field.attrs.remove(pos);
+            let len = attr.parse_args::<LitInt>()?;
+
+            fields.push(BitField {
+                ident,
+                ty: field.ty.clone(),
+                vis: field.vis.clone(),
+                len,
+            });
+        }
+
+        Ok(BitSeqStructInput {
+            item,
+            fields,
+        })
+    }
+}
+
+impl Parse for BitSeqStructArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let backing = if input.is_empty() {
+            None
+        } else {
+            Some(input.parse::<Type>()?)
+        };
+
+        Ok(BitSeqStructArgs {
+            backing,
+        })
+    }
+}
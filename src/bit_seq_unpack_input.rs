@@ -0,0 +1,49 @@
+use syn::{Expr, Result, Token};
+use syn::parse::{Parse, ParseStream};
+
+use crate::bit_seq_input::BitSeqInput;
+
+pub struct BitSeqUnpackInput {
+    /// Whether the fields are declared as variables (`let word => ...`).
+    is_let: bool,
+    value: Expr,
+    pattern: BitSeqInput,
+}
+
+impl BitSeqUnpackInput {
+    pub fn is_let(&self) -> bool {
+        self.is_let
+    }
+
+    pub fn value(&self) -> &Expr {
+        &self.value
+    }
+
+    pub fn pattern(&self) -> &BitSeqInput {
+        &self.pattern
+    }
+}
+
+impl Parse for BitSeqUnpackInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let is_let = input.peek(Token![let]);
+        if is_let {
+            input.parse::<Token![let]>()?;
+        }
+
+        let value = input.parse::<Expr>()?;
+
+        if !input.peek(Token![=>]) {
+            return Err(input.error("expected `=>` followed by the bit sequence pattern"));
+        }
+        input.parse::<Token![=>]>()?;
+
+        let pattern = input.parse::<BitSeqInput>()?;
+
+        Ok(BitSeqUnpackInput {
+            is_let,
+            value,
+            pattern,
+        })
+    }
+}
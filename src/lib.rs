@@ -23,15 +23,93 @@
 //! assert_eq!(t, 0b0110_01_0_1);
 //! ```
 //!
+//! Underscores may separate the bits, and an optional `0b` prefix makes leading zeros explicit.
+//!
+//! ```
+//! use bit_seq::bseq;
+//!
+//! let t = bseq!(0110_1010 0b0001);
+//! assert_eq!(t, 0b0110_1010_0001);
+//! ```
+//!
 //! ## Hex Values
 //!
-//! Hexadecimal values are interpreted as 4-bit sequences.
+//! Hexadecimal values are interpreted as 4-bit sequences, octal values as 3-bit sequences.
 //!
 //! ```
 //! use bit_seq::bseq;
 //!
 //! let t = bseq!(01 0x1f);
 //! assert_eq!(t, 0b01_0001_1111);
+//!
+//! let t = bseq!(0xff_ff 0o17);
+//! assert_eq!(t, 0b11111111_11111111_001_111);
+//! ```
+//!
+//! ## Sized Literals
+//!
+//! Sized literals in Verilog notation take the form `<len>'<radix><value>`, where the radix is `b`, `o`, `d`
+//! or `h`. The value is padded with zeros to `<len>` bits and must not be wider than that.
+//!
+//! ```
+//! use bit_seq::bseq;
+//!
+//! let t = bseq!(4'b1010 8'h0F 6'd42);
+//! assert_eq!(t, 0b1010_00001111_101010);
+//! ```
+//!
+//! ## Repetition
+//!
+//! A segment followed by `{<count>}` is repeated `<count>` times, where `<count>` is an integer literal or
+//! a constant expression of type `usize`. Multiple segments can be grouped in brackets to repeat them together.
+//! The segments of a constant count are evaluated once, and their width must be known at compile time, so they
+//! cannot contain fill segments, don't-care bits or lengths that are only known at runtime.
+//!
+//! ```
+//! use bit_seq::bseq;
+//!
+//! const N: usize = 8;
+//! let x = 0b10;
+//! assert_eq!(bseq!(1{8}), 0xff);
+//! assert_eq!(bseq!(1{N} 0{N - 4}), 0xff0);
+//! assert_eq!(bseq!([01 x:2]{4}), 0b0110_0110_0110_0110);
+//! assert_eq!(bseq!([01 x:2]{N / 2}), 0b0110_0110_0110_0110);
+//! ```
+//!
+//! ## Fill Segments
+//!
+//! In the sized macros, `..0` and `..1` take up all bits that are left by the other segments.
+//!
+//! ```
+//! use bit_seq::{bseq_8, bseq_32};
+//!
+//! assert_eq!(bseq_32!(1101 ..0), 0b1101 << 28);
+//! assert_eq!(bseq_8!(..1 00), 0b111111_00);
+//! ```
+//!
+//! The left-justified macros [`bseq_8_left!`](bseq_8_left!) to [`bseq_128_left!`](bseq_128_left!) shift the
+//! whole sequence up against the most significant bit instead, which is the same as ending it with `..0`.
+//!
+//! ```
+//! use bit_seq::bseq_16_left;
+//!
+//! let rd = 0b101;
+//! assert_eq!(bseq_16_left!(1 rd:3), 0b1_101 << 12);
+//! ```
+//!
+//! ## Type Suffixes
+//!
+//! Integer suffixes are not part of the width, so `0x0fu8` takes up 8 bits. A suffix on any segment sets the
+//! type of the whole sequence, casting all values like the sized macros [`bseq_8!`](bseq_8!) to
+//! [`bseq_128!`](bseq_128!). This includes [`bseq_strict!`](bseq_strict!) and both the mask and the value of
+//! [`bseq_mask!`](bseq_mask!). All suffixes within a sequence must be the same.
+//!
+//! ```
+//! use bit_seq::bseq;
+//!
+//! let x: u8 = 0xa;
+//! let t = bseq!(0xffu64 x:8);
+//! assert_eq!(t, 0xff_0a_u64);
 //! ```
 //!
 //! ## Length Expressions
@@ -45,6 +123,29 @@
 //! assert_eq!(t, 0b1_0_11);
 //! ```
 //!
+//! The length may also be a constant expression in braces, such as a named constant or a const generic.
+//! It is evaluated at compile time.
+//!
+//! ```
+//! use bit_seq::bseq;
+//!
+//! fn encode<const W: usize>(x: u32) -> u32 {
+//!     bseq!(1 x:{W} 0:{W * 2})
+//! }
+//! assert_eq!(encode::<2>(0xf), 0b1_11_0000);
+//! ```
+//!
+//! A length in parentheses is evaluated at runtime and must be of type `usize`.
+//! As the total width is only known at runtime, the macro panics if it exceeds the number of bits of the result type.
+//!
+//! ```
+//! use bit_seq::bseq_16;
+//!
+//! let len = 3;
+//! let t = bseq_16!(1 0xff:(len) 0:(len - 1));
+//! assert_eq!(t, 0b1_111_00);
+//! ```
+//!
 //! ## Variable Interpolation
 //!
 //! Variable interpolation is supported for length expressions.
@@ -56,6 +157,40 @@
 //! assert_eq!(t, 0b10_11);
 //! ```
 //!
+//! Besides variables, length expressions accept field accesses, method calls, index expressions and casts.
+//! Any other expression must be parenthesized.
+//!
+//! ```
+//! use bit_seq::bseq;
+//! let regs = [0b10, 0b01];
+//! let (a, b) = (1, 2);
+//! let t = bseq!(regs[0]:2 (a + b):2);
+//! assert_eq!(t, 0b10_11);
+//! ```
+//!
+//! Constants can be referenced by their path, including associated constants.
+//! Note that `::` separates path segments while a single `:` separates the value from its width.
+//!
+//! ```
+//! use bit_seq::bseq;
+//! mod isa {
+//!     pub const OP_LOAD: u32 = 0b0000011;
+//! }
+//! let rd = 5;
+//! let t = bseq!(isa::OP_LOAD:7 rd:5 u32::MAX:2);
+//! assert_eq!(t, 0b0000011_00101_11);
+//! ```
+//!
+//! Each expression is evaluated exactly once, in the order the segments are written, so segments may read
+//! from a FIFO or an iterator.
+//!
+//! ```
+//! use bit_seq::bseq;
+//! let mut fifo = [0xa, 0xb].into_iter();
+//! let t = bseq!(fifo.next().unwrap():4 fifo.next().unwrap():4);
+//! assert_eq!(t, 0xab);
+//! ```
+//!
 //! ## Unary Operations
 //!
 //! The bseq syntax supports unary operations for length expressions. This simplifies bit sequences like
@@ -71,22 +206,203 @@
 //! assert_eq!(bseq!(-var:8), 0xff);
 //! ```
 //!
+//! ## Exact Width
+//!
+//! A sequence starting with `@N;` must take up exactly `N` bits, which catches fields of the wrong width.
+//! Constant lengths are checked at compile time, lengths in parentheses at runtime.
+//!
+//! ```
+//! use bit_seq::bseq;
+//! let (rd, imm) = (5, 0x1fffff);
+//! let t = bseq!(@32; 0110 rd:5 imm:21 11);
+//! assert_eq!(t, 0b0110_00101_111111111111111111111_11);
+//! ```
+//!
+//! ```compile_fail
+//! use bit_seq::bseq;
+//! const IMM_LEN: usize = 20;
+//! let (rd, imm) = (5, 0x1fffff);
+//! let t = bseq!(@32; 0110 rd:5 imm:{IMM_LEN} 11);
+//! ```
+//!
+//! ## Strict Mode
+//!
+//! Values are silently truncated to their width, so `bseq!(3:1)` is `1`. `bseq_strict!`, or any `bseq`
+//! macro starting with `@strict;`, rejects literals that are wider than their width at compile time and
+//! checks all other values with a `debug_assert!`.
+//!
+//! ```
+//! use bit_seq::{bseq_8, bseq_strict};
+//! let rd = 5;
+//! assert_eq!(bseq_strict!(11 rd:5), 0b11_00101);
+//! assert_eq!(bseq_8!(@strict; 3:2 rd:6), 0b11_000101);
+//! ```
+//!
+//! ## LSB-First Order
+//!
+//! A sequence starting with `@lsb;` lists its segments from the least significant bit up, as in DEFLATE
+//! streams: the first segment lands at bit 0 and later segments go higher. Each segment keeps its own value,
+//! so raw bits like `110` are still the number `0b110`. Flags can be combined, like `@lsb; @strict;`.
+//! Patterns accept the flags as well, and unpacked fields are returned in the order they are written.
+//!
+//! ```
+//! use bit_seq::{bseq, bseq_8, bseq_unpack};
+//! let (bfinal, btype) = (1, 0b10);
+//! assert_eq!(bseq!(@lsb; bfinal:1 btype:2), 0b10_1);
+//! assert_eq!(bseq_8!(@lsb; 110 ..0), 0b00000_110);
+//! assert_eq!(bseq_unpack!(0b10_1u8 => @lsb; bfinal:1 btype:2), (1, 0b10));
+//! ```
+//!
+//! ## Signed Values
+//!
+//! [`bseq_i8!`](bseq_i8!) to [`bseq_i128!`](bseq_i128!) create sequences of the signed types `i8` to `i128`.
+//! A segment like `imm:s12` holds a signed value of 12 bits. If it is the most significant segment of a
+//! sized or typed sequence, its sign bit is extended to the bits above the sequence. Elsewhere, and in
+//! untyped sequences, it contributes its lower bits like `imm:12`. In strict mode, the value is checked
+//! against the range of a signed value of that width, like `-2048..=2047` for `s12`.
+//!
+//! ```
+//! use bit_seq::{bseq_32, bseq_i16, bseq_i32};
+//! let imm = -4;
+//! assert_eq!(bseq_i32!(imm:s12 0:5), -4 << 5);
+//! assert_eq!(bseq_32!(imm:s12 0:5), (-4i32 << 5) as u32);
+//! assert_eq!(bseq_i16!(@strict; 0xfff:12 imm:s4), -4);
+//! ```
+//!
+//! ## Target Types
+//!
+//! [`bseq_as!`](bseq_as!) takes the type of the sequence before the segments. Besides integer types, this can
+//! be a type alias or an integer type wrapped in `Wrapping` or `NonZero`.
+//!
+//! ```
+//! use bit_seq::bseq_as;
+//! use std::num::Wrapping;
+//!
+//! type RegWord = u32;
+//! assert_eq!(bseq_as!(RegWord; 0110 0:28), 0x6000_0000);
+//! assert_eq!(bseq_as!(Wrapping<u8>; ..1) + Wrapping(1), Wrapping(0));
+//! ```
+//!
+//! ## Pattern Matching
+//!
+//! [`bseq_match!`](bseq_match!) uses the same syntax to decode bit sequences again. Raw bits, hex values and
+//! length expressions are matched against the value, while identifiers in length expressions are bound to
+//! the extracted fields.
+//!
+//! ```
+//! use bit_seq::bseq_match;
+//!
+//! let word: u32 = 0b0110_00011_000000000101;
+//! let (rd, imm) = bseq_match!(word {
+//!     0110 rd:5 imm:12 => (rd, imm),
+//!     _ => panic!("unknown instruction"),
+//! });
+//! assert_eq!((rd, imm), (3, 5));
+//! ```
+//!
+//! Patterns may contain don't-care bits, written as `x`. [`bseq_matches!`](bseq_matches!) checks a value
+//! against such a pattern, and [`bseq_mask!`](bseq_mask!) returns the mask and value the pattern is made of.
+//!
+//! ```
+//! use bit_seq::{bseq_mask, bseq_matches};
+//!
+//! assert!(bseq_matches!(0b0110_100u8, 01xx 1x0));
+//! assert_eq!(bseq_mask!(01xx 1x0), (0b1100_101, 0b0100_100));
+//! ```
+//!
+//! If only a single layout is expected, [`bseq_unpack!`](bseq_unpack!) splits a value into its fields directly.
+//!
+//! ```
+//! use bit_seq::bseq_unpack;
+//!
+//! let word: u32 = 0b01_101_00011_00000101;
+//! bseq_unpack!(let word => 01 op:3 rd:5 imm:8);
+//! assert_eq!((op, rd, imm), (0b101, 3, 5));
+//! ```
+//!
+//! ## Bitfield Structs
+//!
+//! The [`#[bit_seq]`](macro@bit_seq) attribute lays out the fields of a struct like the segments of a `bseq!` call,
+//! and generates the code to pack and unpack it.
+//!
+//! ```
+//! use bit_seq::bit_seq;
+//!
+//! #[bit_seq]
+//! struct Ctrl {
+//!     #[bits(3)] mode: u8,
+//!     #[bits(1)] en: bool,
+//!     #[bits(4)] _reserved: u8,
+//! }
+//!
+//! let ctrl = Ctrl { mode: 5, en: true, _reserved: 0 };
+//! assert_eq!(ctrl.pack(), 0b101_1_0000);
+//! ```
+//!
+//! ## Bit Enums
+//!
+//! Enums deriving [`BitEnum`](derive@BitEnum) have a fixed width and can be used as segments without a length.
+//!
+//! ```
+//! use bit_seq::{bseq_8, BitEnum};
+//!
+//! #[derive(BitEnum)]
+//! #[bits(2)]
+//! enum Mode { Slow, Normal, Fast }
+//!
+//! assert_eq!(bseq_8!(1 Mode::Fast 0:5), 0b1_10_00000);
+//! ```
+//!
 //! # Performance
 //!
 //! The `bseq!` macro compiles down to standard bit manipulation operations, meaning there is no runtime overhead to using it.
+//!
+//! Adjacent constant segments, such as raw bits, hex values and literals with a width, are folded into a single
+//! literal. A sequence of such segments expands to one literal, so it can be used in patterns, array lengths and
+//! enum discriminants. Without a type, a zero length expression or hex value like `0:9` above all set bits keeps
+//! its mask, which checks its width against the inferred type, so it is no literal. [`bseq_lit!`](bseq_lit!)
+//! requires all segments to be constant and additionally accepts inclusive ranges for range patterns.
+//!
+//! ```
+//! use bit_seq::{bseq, bseq_8};
+//!
+//! #[repr(u8)]
+//! enum Opcode {
+//!     Load = bseq_8!(0000 011),
+//!     Store = bseq_8!(0x2:4 011),
+//! }
+//!
+//! let op = match 0b01_0001_1111 {
+//!     bseq!(01 0x1f) => Opcode::Load,
+//!     _ => Opcode::Store,
+//! };
+//! assert_eq!(op as u8, 0b0000_011);
+//! ```
 
 
 use proc_macro::TokenStream;
 
 use proc_macro_error::*;
 use quote::{quote, quote_spanned};
-use syn::{LitInt, parse_macro_input, parse_quote, Type};
+use syn::{Ident, LitInt, parse_macro_input, parse_quote, parse_quote_spanned, Type};
 use syn::__private::TokenStream2;
 use syn::spanned::Spanned;
 
-use crate::bit_seq_input::{BitSegment::{self, *}, BitSeqInput};
+use crate::bit_enum_input::BitEnumInput;
+use crate::bit_seq_as_input::BitSeqAsInput;
+use crate::bit_seq_input::{BitSegment::{self, *}, BitSeqInput, BitString, reverse_segments, SegmentLen};
+use crate::bit_seq_lit_input::BitSeqLitInput;
+use crate::bit_seq_match_input::{BitSeqMatchesInput, BitSeqMatchInput};
+use crate::bit_seq_struct_input::{BitSeqStructArgs, BitSeqStructInput};
+use crate::bit_seq_unpack_input::BitSeqUnpackInput;
 
+mod bit_enum_input;
+mod bit_seq_as_input;
 mod bit_seq_input;
+mod bit_seq_lit_input;
+mod bit_seq_match_input;
+mod bit_seq_struct_input;
+mod bit_seq_unpack_input;
 
 
 /// `bseq` is a procedural macro for creating bit sequences.
@@ -137,6 +453,17 @@ mod bit_seq_input;
 /// assert_eq!(t, 0b10_11);
 /// ```
 ///
+/// Field accesses, method calls, index expressions and casts can be used as well.
+/// Other expressions, such as binary operations, must be parenthesized.
+/// ```
+/// use bit_seq::bseq;
+/// struct Reg { flags: u8 }
+/// let reg = Reg { flags: 0b101 };
+/// let (a, b) = (1, 2);
+/// let t = bseq!(reg.flags:3 (a + b):2);
+/// assert_eq!(t, 0b101_11);
+/// ```
+///
 /// ## Unary Operations
 ///
 /// The bseq syntax supports unary operations for length expressions. This simplifies bit sequences like
@@ -163,6 +490,86 @@ pub fn bseq(input: TokenStream) -> TokenStream {
     process(input, None)
 }
 
+/// `bseq_strict` creates a bit sequence like [`bseq!`](bseq!), but rejects values that do not fit into their width.
+///
+/// By default, the value of a length expression is silently truncated to its width, so `bseq!(3:1)` is `1`.
+/// In strict mode, an integer literal that is wider than its width fails to compile, and all other values
+/// are checked by a `debug_assert!`. Unary operations such as `!0:6` or `-1:8` are meant to fill their width
+/// and are never checked.
+///
+/// The same checks are enabled in all other `bseq` macros by starting the sequence with `@strict;`.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::{bseq_16, bseq_strict};
+///
+/// let rd = 5;
+/// assert_eq!(bseq_strict!(3:2 rd:5 !0:3), 0b11_00101_111);
+/// assert_eq!(bseq_16!(@strict; 0xab:8 rd:8), 0xab05);
+/// ```
+///
+/// ```compile_fail
+/// use bit_seq::bseq_strict;
+/// let t = bseq_strict!(3:1);
+/// ```
+///
+/// ```should_panic
+/// use bit_seq::bseq_strict;
+/// let rd = 32;
+/// let t = bseq_strict!(rd:5);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_strict(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as BitSeqInput);
+    input.set_strict();
+    let var_type = suffix_type(input.segments());
+    expand(&input, &var_type).into()
+}
+
+/// `bseq_lit` creates a bit sequence like [`bseq!`](bseq!), but expands to a single integer literal.
+///
+/// As a literal, the sequence can be used where an expression cannot, such as in patterns and const generic
+/// arguments. All segments must be known at expansion time: raw bits, hex, octal and sized literals, and integer
+/// literals with a width, optionally with a unary operation like `!0:6`. The literal has the type given by an
+/// integer suffix, or is inferred otherwise.
+///
+/// Rust does not accept macros as the bounds of a range pattern, so an inclusive range is written within the
+/// macro instead, like `bseq_lit!(0000 ..= 0111)`, and expands to the range pattern of both literals. Flags
+/// are written before the start and apply to both ends, like `bseq_lit!(@lsb; 0 0 0 1 ..= 1 0 1 1)`.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::bseq_lit;
+///
+/// fn decode(op: u8) -> &'static str {
+///     match op {
+///         bseq_lit!(0110 01) => "load",
+///         bseq_lit!(0000 ..= 0111) => "alu",
+///         _ => "unknown",
+///     }
+/// }
+/// assert_eq!(decode(0b0110_01), "load");
+/// assert_eq!(decode(0b0101), "alu");
+///
+/// struct Reg<const ID: u8>;
+/// let _: Reg<{ bseq_lit!(1 0x2:4) }> = Reg::<0b1_0010>;
+/// ```
+///
+/// Segments that are not known at expansion time fail to compile:
+/// ```compile_fail
+/// use bit_seq::bseq_lit;
+/// let rd = 5;
+/// let t = bseq_lit!(0110 rd:5);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_lit(input: TokenStream) -> TokenStream {
+    process_lit(input)
+}
+
 /// The `bseq_8` procedural macro is specifically tailored for creating 8-bit sequences.
 ///
 /// It is primarily utilized when there's a need to accommodate variable types different from those
@@ -184,9 +591,16 @@ pub fn bseq(input: TokenStream) -> TokenStream {
 /// let t: u8 = bseq_8!(foo:5 bar:3);
 /// ```
 ///
+/// A bit sequence wider than 8 bits fails to compile, with an error pointing to the segment that exceeds the type:
+/// ```compile_fail
+/// use bit_seq::bseq_8;
+/// let t = bseq_8!(0xff 1);
+/// ```
+///
 /// It is important to note that `bseq_8` effectively performs as `bseq!(...)`, albeit with intermediate type casts.
 /// For a comprehensive understanding on the usage of `bseq_8`, please refer to the [`bseq!`](bseq!) documentation.
 #[proc_macro]
+#[proc_macro_error]
 pub fn bseq_8(input: TokenStream) -> TokenStream {
     let ty: Type = parse_quote!(u8);
     process(input, Some(ty))
@@ -216,6 +630,7 @@ pub fn bseq_8(input: TokenStream) -> TokenStream {
 /// It is important to note that `bseq_16` effectively performs as `bseq!(...)`, albeit with intermediate type casts.
 /// For a comprehensive understanding on the usage of `bseq_16`, please refer to the [`bseq!`](bseq!) documentation.
 #[proc_macro]
+#[proc_macro_error]
 pub fn bseq_16(input: TokenStream) -> TokenStream {
     let ty: Type = parse_quote!(u16);
     process(input, Some(ty))
@@ -245,6 +660,7 @@ pub fn bseq_16(input: TokenStream) -> TokenStream {
 /// It is important to note that `bseq_32` effectively performs as `bseq!(...)`, albeit with intermediate type casts.
 /// For a comprehensive understanding on the usage of `bseq_32`, please refer to the [`bseq!`](bseq!) documentation.
 #[proc_macro]
+#[proc_macro_error]
 pub fn bseq_32(input: TokenStream) -> TokenStream {
     let ty: Type = parse_quote!(u32);
     process(input, Some(ty))
@@ -274,6 +690,7 @@ pub fn bseq_32(input: TokenStream) -> TokenStream {
 /// Note that `bseq_64` is essentially `bseq!(...)` with intermediate type casts. For details on how to use `bseq_64`,
 /// please refer to the [`bseq!`](bseq!) documentation.
 #[proc_macro]
+#[proc_macro_error]
 pub fn bseq_64(input: TokenStream) -> TokenStream {
     let ty: Type = parse_quote!(u64);
     process(input, Some(ty))
@@ -300,77 +717,2097 @@ pub fn bseq_64(input: TokenStream) -> TokenStream {
 /// let t: u128 = bseq_128!(foo:5 bar:59);
 /// ```
 ///
+/// Segments may cover the full width of 128 bits, while wider segments fail to compile:
+/// ```
+/// use bit_seq::bseq_128;
+/// let max = u128::MAX;
+/// assert_eq!(bseq_128!(max:128), u128::MAX);
+/// assert_eq!(bseq_128!(1 0:127), 1 << 127);
+/// ```
+///
 /// Note that `bseq_128` is essentially `bseq!(...)` with intermediate type casts. For details on how to use `bseq_128`,
 /// please refer to the [`bseq!`](bseq!) documentation.
 #[proc_macro]
+#[proc_macro_error]
 pub fn bseq_128(input: TokenStream) -> TokenStream {
     let ty: Type = parse_quote!(u128);
     process(input, Some(ty))
 }
 
-/// Processes the `bseq` input stream with a specified variable type.
+/// The `bseq_8_left` procedural macro creates an 8-bit sequence like [`bseq_8!`](bseq_8!), but left-justified.
 ///
-/// `bseq!` has variable type None \
-/// `bseq8!` has variable type Option<Type<u8>> \
-/// ...
-fn process(input: TokenStream, var_type: Option<Type>) -> TokenStream {
-    // parse input
-    let input = parse_macro_input!(input as BitSeqInput);
+/// Instead of packing the segments against bit 0, the whole sequence is shifted up so that its first segment
+/// starts at the most significant bit of the `u8`. The bits below the sequence are `0`.
+///
+/// ```
+/// use bit_seq::bseq_8_left;
+/// let rd = 2;
+/// assert_eq!(bseq_8_left!(1 rd:3), 0b1_010_0000);
+/// ```
+///
+/// A bit sequence wider than 8 bits fails to compile:
+/// ```compile_fail
+/// use bit_seq::bseq_8_left;
+/// let t = bseq_8_left!(0xff 1);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_8_left(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(u8);
+    process_left(input, ty)
+}
 
-    // construct shift token streams
-    let mut bit_len = 0;
-    let shifts: Vec<_> = input.segments()
-        .iter().rev()
-        .map(|seg| map_segment(seg, &mut bit_len, &var_type))
-        .collect();
+/// The `bseq_16_left` procedural macro creates a 16-bit sequence like [`bseq_16!`](bseq_16!), but left-justified.
+///
+/// The first segment starts at the most significant bit of the `u16`, and the bits below the sequence are `0`.
+/// Sequences wider than 16 bits fail to compile.
+///
+/// ```
+/// use bit_seq::bseq_16_left;
+/// let addr = 0x1f;
+/// assert_eq!(bseq_16_left!(1 addr:7), 0b1_0011111_00000000);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_16_left(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(u16);
+    process_left(input, ty)
+}
 
-    // combine all shift segments
-    let span = proc_macro2::Span::call_site();
+/// The `bseq_32_left` procedural macro creates a 32-bit sequence like [`bseq_32!`](bseq_32!), but left-justified.
+///
+/// The first segment starts at the most significant bit of the `u32`, and the bits below the sequence are `0`.
+/// This is the layout of SPI frames that are shifted out MSB first. Sequences wider than 32 bits fail to compile.
+///
+/// ```
+/// use bit_seq::bseq_32_left;
+/// let reg = 0x2a;
+/// assert_eq!(bseq_32_left!(0 reg:7), 0x2a << 24);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_32_left(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(u32);
+    process_left(input, ty)
+}
 
-    let mut macro_out = if let Some(ty) = var_type {
-        quote!((#(#shifts)|*) as #ty)
-    } else {
-        quote!(#(#shifts)|*)
-    };
+/// The `bseq_64_left` procedural macro creates a 64-bit sequence like [`bseq_64!`](bseq_64!), but left-justified.
+///
+/// The first segment starts at the most significant bit of the `u64`, and the bits below the sequence are `0`.
+/// Sequences wider than 64 bits fail to compile.
+///
+/// ```
+/// use bit_seq::bseq_64_left;
+/// assert_eq!(bseq_64_left!(0xdead 0xbeef), 0xdead_beef_0000_0000);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_64_left(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(u64);
+    process_left(input, ty)
+}
 
-    if macro_out.is_empty() {
-        // if no input provided, result is 0
-        macro_out = quote_spanned!(span=> 0);
-    }
+/// The `bseq_128_left` procedural macro creates a 128-bit sequence like [`bseq_128!`](bseq_128!), but left-justified.
+///
+/// The first segment starts at the most significant bit of the `u128`, and the bits below the sequence are `0`.
+/// Sequences wider than 128 bits fail to compile.
+///
+/// ```
+/// use bit_seq::bseq_128_left;
+/// assert_eq!(bseq_128_left!(1), 1 << 127);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_128_left(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(u128);
+    process_left(input, ty)
+}
 
-    macro_out.into()
+/// The `bseq_i8` procedural macro creates an 8-bit sequence like [`bseq_8!`](bseq_8!), but of type `i8`.
+///
+/// The segments are laid out as in `bseq_8!`, and the bits of the sequence are then reinterpreted as `i8`,
+/// so a sequence whose most significant bit is set is negative. A signed value with a width like `imm:s4`
+/// that is the most significant segment extends its sign to the bits above the sequence.
+///
+/// ```
+/// use bit_seq::bseq_i8;
+/// let imm = -3;
+/// assert_eq!(bseq_i8!(1 0:7), i8::MIN);
+/// assert_eq!(bseq_i8!(imm:s4 01), -3 << 2 | 0b01);
+/// ```
+///
+/// A bit sequence wider than 8 bits fails to compile:
+/// ```compile_fail
+/// use bit_seq::bseq_i8;
+/// let t = bseq_i8!(0xff 1);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_i8(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(i8);
+    process(input, Some(ty))
 }
 
+/// The `bseq_i16` procedural macro creates a 16-bit sequence like [`bseq_16!`](bseq_16!), but of type `i16`.
+///
+/// The bits of the sequence are reinterpreted as `i16`, and a signed value like `imm:s12` that is the most
+/// significant segment extends its sign to the bits above the sequence.
+///
+/// ```
+/// use bit_seq::bseq_i16;
+/// let offset = -100;
+/// assert_eq!(bseq_i16!(offset:s12), -100);
+/// assert_eq!(bseq_i16!(0xffff), -1);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_i16(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(i16);
+    process(input, Some(ty))
+}
 
-fn map_segment(seg: &BitSegment, curr_bit_len: &mut usize, expr_type: &Option<Type>) -> TokenStream2 {
-    let (val, len) = match seg {
-        Bits(bits) => {
-            let b = bits.to_string();
-            let num = usize::from_str_radix(&b, 2).unwrap();
-            let num_lit = LitInt::new(&num.to_string(), b.span());
-            let span = bits.span();
-            let rep = quote_spanned!(span=> #num_lit);
-            (rep, b.len())
-        }
-        Expr(expr, len_lit) => {
-            let len: usize = len_lit.base10_parse().unwrap_or_else(|_| abort!(len_lit, "Couldn't be parsed!"));
-            let mask: u128 = (1 << len) - 1;
-            let mask_lit = LitInt::new(&mask.to_string(), expr.span());
+/// The `bseq_i32` procedural macro creates a 32-bit sequence like [`bseq_32!`](bseq_32!), but of type `i32`.
+///
+/// The bits of the sequence are reinterpreted as `i32`, and a signed value like `imm:s12` that is the most
+/// significant segment extends its sign to the bits above the sequence. This decodes the immediates of
+/// instruction sets like RISC-V, which are split into several fields.
+///
+/// ```
+/// use bit_seq::bseq_i32;
+/// // the immediate of a RISC-V branch, whose bits 12 and 10 to 5 are in the upper part of the instruction
+/// let (imm_12, imm_10_5, imm_4_1, imm_11) = (-1, 0b111111, 0b1100, 1);
+/// assert_eq!(bseq_i32!(imm_12:s1 imm_11:1 imm_10_5:6 imm_4_1:4 0), -8);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_i32(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(i32);
+    process(input, Some(ty))
+}
+
+/// The `bseq_i64` procedural macro creates a 64-bit sequence like [`bseq_64!`](bseq_64!), but of type `i64`.
+///
+/// The bits of the sequence are reinterpreted as `i64`, and a signed value like `imm:s12` that is the most
+/// significant segment extends its sign to the bits above the sequence.
+///
+/// ```
+/// use bit_seq::bseq_i64;
+/// let (hi, lo) = (-2, 0xabc);
+/// assert_eq!(bseq_i64!(hi:s20 lo:12), -2 << 12 | 0xabc);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_i64(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(i64);
+    process(input, Some(ty))
+}
+
+/// The `bseq_i128` procedural macro creates a 128-bit sequence like [`bseq_128!`](bseq_128!), but of type `i128`.
+///
+/// The bits of the sequence are reinterpreted as `i128`, and a signed value like `imm:s12` that is the most
+/// significant segment extends its sign to the bits above the sequence.
+///
+/// ```
+/// use bit_seq::bseq_i128;
+/// let x = -1;
+/// assert_eq!(bseq_i128!(x:s64 0:64), -1 << 64);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_i128(input: TokenStream) -> TokenStream {
+    let ty: Type = parse_quote!(i128);
+    process(input, Some(ty))
+}
+
+/// The `bseq_as` procedural macro creates a bit sequence of the type given before the segments, like
+/// `bseq_as!(u32; 0110 rd:5)`.
+///
+/// The type may be any integer type, a type alias, or an integer type wrapped in `Wrapping` or `NonZero`
+/// (including `NonZeroU8` to `NonZeroUsize`). As in the sized macros, all segments are cast to the integer
+/// type before they are shifted. If it is a primitive type like `u32` or `i16`, the sequence is checked against
+/// its width, as in [`bseq_32!`](bseq_32!) and [`bseq_i16!`](bseq_i16!). The width of any other type, like an
+/// alias or `usize`, is only known to the compiler, so the sequence is checked against its `BITS` in a constant
+/// assertion instead, and fill segments extend to `BITS`. For these types, a signed most significant segment
+/// does not extend its sign to the bits above the sequence.
+///
+/// ```
+/// use bit_seq::bseq_as;
+/// use std::num::{NonZeroU8, Wrapping};
+///
+/// type RegWord = u32;
+/// let rd = 3;
+/// assert_eq!(bseq_as!(RegWord; 0110 rd:5), 0b0110_00011);
+/// assert_eq!(bseq_as!(i8; ..1), -1);
+/// assert_eq!(bseq_as!(Wrapping<u16>; 0xff ..1), Wrapping(0xffff));
+/// assert_eq!(bseq_as!(NonZeroU8; 1 rd:2).get(), 0b1_11);
+///
+/// type Imm = i16;
+/// assert_eq!(bseq_as!(Imm; 1 ..0), i16::MIN);
+/// assert_eq!(bseq_as!(usize; ..1), usize::MAX);
+/// ```
+///
+/// A `NonZero` sequence panics if all of its bits are 0. A sequence wider than its type fails to compile:
+/// ```compile_fail
+/// use bit_seq::bseq_as;
+/// let t = bseq_as!(u8; 0xff 1);
+/// ```
+/// ```compile_fail
+/// use bit_seq::bseq_as;
+/// type Byte = u8;
+/// let x = 0;
+/// let t = bseq_as!(Byte; 0xff x:1);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_as(input: TokenStream) -> TokenStream {
+    process_as(input)
+}
+
+/// `bseq_match` is the pattern matching counterpart to [`bseq!`](bseq!).
+///
+/// It takes a value followed by `match`-like arms, where each arm pattern is a bit sequence
+/// written in the `bseq!` syntax. The segments of a pattern are laid out exactly as `bseq!` would lay
+/// them out, so a value created by `bseq!` is matched by the same sequence.
+///
+/// - Raw bits, hex values and length expressions with literals or unary operations are the fixed part of
+///   the pattern. An arm only matches if the value contains these bits at the respective positions.
+/// - Don't-care bits, written as `x` (e.g. `01xx`), match any bit.
+/// - Length expressions with a plain identifier, such as `rd:5`, bind the extracted field to that identifier.
+///   The bound field has the type of the matched value.
+///
+/// Arms are tested from top to bottom and may have an `if` guard. The wildcard `_` matches everything.
+/// As with `match`, the macro fails to compile if not every value is covered, so a `_` arm is usually required.
+/// A pattern wider than the matched value panics.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::bseq_match;
+///
+/// fn decode(word: u32) -> String {
+///     bseq_match!(word {
+///         0110 rd:5 imm:12 => format!("load r{} {}", rd, imm),
+///         0111 rd:5 rs:5 _pad:7 if rd == rs => format!("nop r{}", rd),
+///         0111 rd:5 rs:5 _pad:7 => format!("move r{} r{}", rd, rs),
+///         _ => format!("unknown"),
+///     })
+/// }
+///
+/// assert_eq!(decode(0b0110_00011_000000000101), "load r3 5");
+/// assert_eq!(decode(0b0111_00011_00011_0000000), "nop r3");
+/// assert_eq!(decode(0b0111_00011_00100_0000000), "move r3 r4");
+/// assert_eq!(decode(0), "unknown");
+/// ```
+///
+/// Hex values and length expressions can be used as fixed bits too:
+/// ```
+/// use bit_seq::bseq_match;
+///
+/// let word: u16 = 0xa5_3f;
+/// let low = bseq_match!(word {
+///     0xa5 low:8 => low,
+///     !0:4 _rest:12 => 0,
+///     _ => 0,
+/// });
+/// assert_eq!(low, 0x3f);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_match(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqMatchInput);
+
+    let value = input.value();
+    let value_ident = Ident::new("__bseq_value", proc_macro2::Span::mixed_site());
+
+    let checks: Vec<_> = input.arms().iter()
+        .filter_map(|arm| arm.pattern.as_ref())
+        .map(|pattern| width_check(pattern, pattern.segments()))
+        .collect();
+    let value_checks: Vec<_> = input.arms().iter()
+        .filter_map(|arm| arm.pattern.as_ref())
+        .map(|pattern| value_check(pattern, &value_ident))
+        .collect();
+    let arms: Vec<_> = input.arms().iter().map(|arm| {
+        let body = &arm.body;
+        let Some(pattern) = &arm.pattern else {
+            // the wildcard stays unguarded, so rustc can prove exhaustiveness
+            return match &arm.guard {
+                Some(guard) => quote!(_ if #guard => #body,),
+                None => quote!(_ => #body,),
+            };
+        };
+
+        let condition = pattern_condition(pattern, &value_ident);
+        // the fields are bound in the guard and the body, so, as in `match`, a field is only reported as unused
+        // if neither of them uses it
+        let (guard, bindings) = match &arm.guard {
+            Some(guard) => {
+                let guard_bindings = pattern_bindings(pattern, &value_ident, |_| true);
+                let bindings = pattern_bindings(pattern, &value_ident, |ident| mentions_ident(quote!(#guard), ident));
+                (quote!(&& { #(#guard_bindings)* #guard }), bindings)
+            }
+            None => (quote!(), pattern_bindings(pattern, &value_ident, |_| false)),
+        };
+
+        quote! {
+            #value_ident if #condition #guard => {
+                #(#bindings)*
+                #body
+            }
+        }
+    }).collect();
+
+    // the scrutinee is located at the value, so rustc reports missing arms there
+    let mut scrutinee = value_ident.clone();
+    scrutinee.set_span(proc_macro2::Span::mixed_site().located_at(value.span()));
+
+    quote!({
+        #(#checks)*
+        let #value_ident = #value;
+        #(#value_checks)*
+        match #scrutinee {
+            #(#arms)*
+        }
+    }).into()
+}
+
+/// `bseq_matches` checks whether a value matches a bit sequence pattern.
+///
+/// Like [`matches!`], it takes the value, a `,`, a pattern and an optional `if` guard.
+/// The pattern is written as in [`bseq_match!`](bseq_match!), so identifiers in length expressions are bound
+/// to the extracted fields and can be used in the guard.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::bseq_matches;
+///
+/// let word: u8 = 0b0110_100;
+/// assert!(bseq_matches!(word, 01xx 1x0));
+/// assert!(!bseq_matches!(word, 00xx xxx));
+/// assert!(bseq_matches!(word, 0 0x3:2 rest:4 if rest == 4));
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_matches(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqMatchesInput);
+
+    let value = input.value();
+    let value_ident = Ident::new("__bseq_value", proc_macro2::Span::mixed_site());
+    let condition = pattern_condition(input.pattern(), &value_ident);
+    let check = width_check(input.pattern(), input.pattern().segments());
+    let value_check = value_check(input.pattern(), &value_ident);
+
+    let guard = match input.guard() {
+        Some(guard) => {
+            let bindings = pattern_bindings(input.pattern(), &value_ident, |_| false);
+            quote!(&& { #(#bindings)* #guard })
+        }
+        None => quote!(),
+    };
+
+    quote!({
+        #check
+        let #value_ident = #value;
+        #value_check
+        #condition #guard
+    }).into()
+}
+
+/// `bseq_mask` returns the mask and value of a bit sequence with don't-care bits.
+///
+/// Don't-care bits are written as `x`, such as in `01xx` or `x1`. As `0x` starts a hex value, a `0` followed
+/// by don't-care bits must be separated, as in `0 xx`. The result is a tuple `(mask, value)`, where
+/// `mask` has all bits set that are not don't-care bits, and `value` is the bit sequence with all don't-care
+/// bits set to `0`. A value `v` thus matches the bit sequence if `v & mask == value`.
+///
+/// Apart from don't-care bits, the input is interpreted exactly as by [`bseq!`](bseq!), so length expressions
+/// interpolate variables and are part of the mask.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::bseq_mask;
+///
+/// let (mask, value) = bseq_mask!(01xx 1x0);
+/// assert_eq!(mask, 0b1100_101);
+/// assert_eq!(value, 0b0100_100);
+///
+/// let op = 3;
+/// let (mask, value): (u16, u16) = bseq_mask!(op:2 xxxx 0xf);
+/// assert_eq!(mask, 0b11_0000_1111);
+/// assert_eq!(value, 0b11_0000_1111);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_mask(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqInput);
+    let segments = input.segments();
+    let var_type = suffix_type(segments);
+
+    let masks: Vec<_> = segments.iter()
+        .zip(segment_offsets(segments))
+        .map(|(seg, offset)| {
+            let span = seg.span();
+            let width = segment_width(seg);
+            let mask = match seg {
+                Bits(bits) => {
+                    let care_lit = LitInt::new(&bits.care_mask().to_string(), span);
+                    quote_spanned!(span=> #care_lit)
+                }
+                _ => mask_tokens(&width, span),
+            };
+            let offset = shift_tokens(&offset, &width, span);
+            quote_spanned!(span=> (#mask) << #offset)
+        })
+        .collect();
+    let mask = if masks.is_empty() {
+        quote!(0)
+    } else {
+        quote!(#(#masks)|*)
+    };
+    // the mask has the type given by a suffix as well, which the masks of all segments are inferred as
+    let mask = match &var_type {
+        Some(ty) => {
+            let mask_ident = Ident::new("__bseq_mask", proc_macro2::Span::mixed_site());
+            quote!({ let #mask_ident: #ty = #mask; #mask_ident })
+        }
+        None => mask,
+    };
+
+    // the value is the bit sequence with all don't-care bits cleared
+    let value_input = input.with_segments(segments.iter()
+        .map(|seg| match seg {
+            Bits(bits) => Bits(bits.without_dont_care()),
+            seg => seg.clone(),
+        })
+        .collect());
+    let value = expand(&value_input, &var_type);
+
+    quote!((#mask, #value)).into()
+}
+
+/// `bseq_unpack` splits a value into the fields of a bit sequence, reversing [`bseq!`](bseq!).
+///
+/// The value is followed by `=>` and a pattern in the `bseq!` syntax. Like in [`bseq_match!`](bseq_match!),
+/// length expressions with a plain identifier are the fields to extract, while all other segments must match
+/// the respective bits of the value. The fields are returned as a tuple in the order of the pattern,
+/// a single field is returned as is. Each field has the type of the unpacked value.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::bseq_unpack;
+///
+/// let word: u32 = 0b01_101_00011_00000101;
+/// let (op, rd, imm) = bseq_unpack!(word => 01 op:3 rd:5 imm:8);
+/// assert_eq!((op, rd, imm), (0b101, 3, 5));
+/// ```
+///
+/// With a leading `let`, the fields are declared as variables instead:
+/// ```
+/// use bit_seq::bseq_unpack;
+///
+/// let word: u16 = 0xa5_3f;
+/// bseq_unpack!(let word => 0xa5 high:2 low:6);
+/// assert_eq!((high, low), (0, 0x3f));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the fixed segments of the pattern do not match the value, or if the pattern is wider than the
+/// value. The generated code is const-evaluable, so within a `const` context a mismatch is reported at compile
+/// time instead:
+/// ```compile_fail
+/// use bit_seq::bseq_unpack;
+///
+/// const RD: u8 = bseq_unpack!(0b10_000011u8 => 01 rd:6);
+/// ```
+///
+/// Use [`bseq_try_unpack!`](bseq_try_unpack!) to handle mismatches at runtime.
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_unpack(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqUnpackInput);
+    process_unpack(input, false)
+}
+
+/// `bseq_try_unpack` is the fallible version of [`bseq_unpack!`](bseq_unpack!).
+///
+/// Instead of panicking, it returns `None` if the fixed segments of the pattern do not match the value.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::bseq_try_unpack;
+///
+/// let word: u8 = 0b01_000011;
+/// assert_eq!(bseq_try_unpack!(word => 01 rd:6), Some(3));
+/// assert_eq!(bseq_try_unpack!(word => 10 rd:6), None);
+/// ```
+#[proc_macro]
+#[proc_macro_error]
+pub fn bseq_try_unpack(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqUnpackInput);
+    if input.is_let() {
+        abort_call_site!("the `let` form is not supported by `bseq_try_unpack!`");
+    }
+    process_unpack(input, true)
+}
+
+/// `bit_seq` is an attribute macro for structs whose fields are packed into a single integer.
+///
+/// Every field requires a `#[bits(N)]` attribute that specifies how many bits it occupies. The fields
+/// are laid out exactly like the segments of a [`bseq!`](bseq!) call, so the first field occupies the most
+/// significant bits and the last field the least significant ones. A struct therefore always packs to the same
+/// value as the equivalent `bseq!` call.
+///
+/// The backing integer can be passed to the attribute, as in `#[bit_seq(u32)]`. By default, the smallest
+/// unsigned integer that fits all fields is used.
+///
+/// The struct itself is left unchanged, while the following items are generated:
+///
+/// - `BITS`, the number of bits occupied by all fields
+/// - `pack(&self)`, which packs the fields into the backing integer
+/// - `unpack(bits)`, which creates the struct from the backing integer, ignoring bits beyond `BITS`
+/// - a getter and a setter (`set_<field>`) for each field, except those starting with `_`.
+///   The setter truncates integers to the width of the field.
+/// - `From<Struct>` for the backing integer and `TryFrom<backing integer>` for the struct.
+///   The conversion fails with the original value if any bits beyond `BITS` are set.
+///
+/// Fields may be of type `bool`, a primitive integer, or an enum deriving [`BitEnum`](derive@BitEnum).
+/// The width of an integer field must not exceed its type. Signed integer fields are stored in two's complement
+/// and sign-extended by `unpack` and their setter, so negative values round-trip.
+/// The width of an enum field must be the `BITS` of the enum. Getters return enum fields by value, so the enum
+/// must be `Copy`. If the bits of an enum field are no valid variant, `unpack` panics and `TryFrom` fails.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::{bit_seq, bseq};
+///
+/// #[bit_seq]
+/// struct Ctrl {
+///     #[bits(3)] mode: u8,
+///     #[bits(1)] en: bool,
+///     #[bits(4)] _reserved: u8,
+/// }
+///
+/// let mut ctrl = Ctrl { mode: 5, en: true, _reserved: 0 };
+/// assert_eq!(Ctrl::BITS, 8);
+/// assert_eq!(ctrl.pack(), bseq!(101 1 0000));
+///
+/// ctrl.set_mode(0b1010);
+/// assert_eq!(ctrl.mode(), 0b010);
+///
+/// let ctrl = Ctrl::unpack(0b011_0_0000);
+/// assert_eq!((ctrl.mode(), ctrl.en()), (3, false));
+/// ```
+///
+/// Conversions from and to an explicitly given backing integer:
+/// ```
+/// use bit_seq::bit_seq;
+///
+/// #[bit_seq(u16)]
+/// struct Status {
+///     #[bits(1)] busy: bool,
+///     #[bits(7)] count: u8,
+/// }
+///
+/// let raw: u16 = Status { busy: true, count: 3 }.into();
+/// assert_eq!(raw, 0b1_0000011);
+///
+/// assert!(Status::try_from(0b1_0000011).is_ok());
+/// assert_eq!(Status::try_from(0x1ff).err(), Some(0x1ff));
+/// ```
+#[proc_macro_attribute]
+#[proc_macro_error]
+pub fn bit_seq(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as BitSeqStructArgs);
+    let input = parse_macro_input!(input as BitSeqStructInput);
+    process_struct(args, input)
+}
+
+/// `BitEnum` derives the conversion of a fieldless enum from and to a fixed-width bit field.
+///
+/// The width is specified with the `#[bits(N)]` attribute. The bits of a variant are its discriminant, so
+/// explicit discriminants are supported. A discriminant that does not fit into `N` bits fails to compile.
+///
+/// The following items are generated:
+///
+/// - `BITS`, the width of the enum
+/// - `to_bits(&self)`, which returns the bits of the variant
+/// - `try_from_bits(bits)`, which returns the variant with the given bits, or `None` if there is no such variant
+///
+/// Both functions are `const` and use the smallest unsigned integer with at least `N` bits.
+///
+/// A `BitEnum` variant can be used as a segment in [`bseq!`](bseq!) and its relatives without a `:len` suffix,
+/// as its width is given by the enum. Its bits are converted to the type of the sequence, which `bseq!` infers. It can also be the type of a field in a [`#[bit_seq]`](macro@bit_seq) struct.
+///
+/// # Examples
+///
+/// ```
+/// use bit_seq::{bseq_16, BitEnum};
+///
+/// #[derive(BitEnum, Debug, PartialEq)]
+/// #[bits(3)]
+/// enum Op {
+///     Load = 1,
+///     Store = 2,
+///     Jump = 7,
+/// }
+///
+/// assert_eq!(Op::BITS, 3);
+/// assert_eq!(Op::Store.to_bits(), 2u8);
+/// assert_eq!(Op::try_from_bits(7), Some(Op::Jump));
+/// assert_eq!(Op::try_from_bits(3), None);
+///
+/// let rd = 5;
+/// assert_eq!(bseq_16!(Op::Jump rd:5), 0b111_00101);
+/// ```
+///
+/// Discriminants must fit into the width:
+/// ```compile_fail
+/// use bit_seq::BitEnum;
+///
+/// #[derive(BitEnum)]
+/// #[bits(2)]
+/// enum Op {
+///     Load = 1,
+///     Jump = 4,
+/// }
+/// ```
+#[proc_macro_derive(BitEnum, attributes(bits))]
+#[proc_macro_error]
+pub fn bit_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitEnumInput);
+    process_enum(input)
+}
+
+/// Processes the `BitEnum` derive input and generates the bit conversions.
+fn process_enum(input: BitEnumInput) -> TokenStream {
+    let name = input.ident();
+    let len_lit = input.len();
+    let len: usize = len_lit.base10_parse().unwrap_or_else(|_| abort!(len_lit, "Couldn't be parsed!"));
+    let Some(bits_ty) = unsigned_type(len) else {
+        abort!(len_lit, "a `BitEnum` can have at most 128 bits, but got {}", len);
+    };
+    let len_lit = LitInt::new(&len.to_string(), len_lit.span());
+    let bits_ident = Ident::new("bits", proc_macro2::Span::mixed_site());
+
+    let to_bits_arms = input.variants().iter().map(|variant| {
+        quote_spanned!(variant.span()=> Self::#variant => Self::#variant as #bits_ty,)
+    });
+
+    let from_bits_checks = input.variants().iter().map(|variant| {
+        quote_spanned! {variant.span()=>
+            if #bits_ident == Self::#variant as #bits_ty {
+                return ::core::option::Option::Some(Self::#variant);
+            }
+        }
+    });
+
+    // every discriminant fits into 128 bits
+    let fit_checks = input.variants().iter().filter(|_| len < 128).map(|variant| {
+        let err = format!("the discriminant of `{}::{}` does not fit into {} bits", name, variant, len);
+        quote_spanned! {variant.span()=>
+            const _: () = ::core::assert!((#name::#variant as u128) >> #len_lit == 0, #err);
+        }
+    });
+
+    quote! {
+        impl #name {
+            /// The number of bits of the enum.
+            pub const BITS: u32 = #len_lit;
+
+            /// Returns the bits of the variant.
+            pub const fn to_bits(&self) -> #bits_ty {
+                match self {
+                    #(#to_bits_arms)*
+                }
+            }
+
+            /// Returns the variant with the given bits, or `None` if there is no such variant.
+            pub const fn try_from_bits(#bits_ident: #bits_ty) -> ::core::option::Option<Self> {
+                #(#from_bits_checks)*
+                ::core::option::Option::None
+            }
+        }
+
+        #(#fit_checks)*
+    }.into()
+}
+
+/// Processes the `#[bit_seq]` struct and generates its packing code.
+fn process_struct(args: BitSeqStructArgs, input: BitSeqStructInput) -> TokenStream {
+    let item = input.item();
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    // lay out the fields as segments of a bit sequence, just like `bseq!(self.a:N self.b:M ...)`
+    let layout = BitSeqInput::new(input.fields().iter().map(|field| {
+        let ident = &field.ident;
+        let expr = match field_kind(&field.ty) {
+            FieldKind::Enum => parse_quote_spanned!(ident.span()=> self.#ident.to_bits()),
+            _ => parse_quote_spanned!(ident.span()=> self.#ident),
+        };
+        Expr(expr, SegmentLen::Lit(field.len.clone()))
+    }).collect());
+    let offsets = segment_offsets(layout.segments());
+    let lens: Vec<usize> = input.fields().iter()
+        .map(|field| {
+            let len = field.len.base10_parse().unwrap_or_else(|_| abort!(field.len, "Couldn't be parsed!"));
+            if let Some(ty_len) = primitive_bits(&field.ty).filter(|ty_len| len > *ty_len) {
+                let ty = &field.ty;
+                abort!(field.len, "the field takes up {} bits, but `{}` only has {} bits", len, quote!(#ty), ty_len);
+            }
+            len
+        })
+        .collect();
+    let bit_len: usize = lens.iter().sum();
+
+    let backing = match args.backing {
+        Some(ty) => {
+            if let Some(ty_len) = primitive_bits(&ty) {
+                if bit_len > ty_len {
+                    abort!(ty, "the fields take up {} bits, but `{}` only has {} bits", bit_len, quote!(#ty), ty_len);
+                }
+            }
+            ty
+        }
+        None => unsigned_type(bit_len).unwrap_or_else(|| {
+            abort!(name, "the fields take up {} bits, but at most 128 bits are supported", bit_len)
+        }),
+    };
+
+    let pack = expand(&layout, &Some(backing.clone()));
+    let bits_ident = Ident::new("bits", proc_macro2::Span::mixed_site());
+    let bit_len_lit = LitInt::new(&bit_len.to_string(), proc_macro2::Span::call_site());
+
+    // the fields extracted from `bits`, where `invalid` handles bits that are no valid enum variant
+    let unpacked_fields = |invalid: TokenStream2| -> Vec<TokenStream2> {
+        input.fields().iter().zip(&offsets).zip(&lens).map(|((field, offset), len)| {
+            let ident = &field.ident;
+            let ty = &field.ty;
+            let span = ident.span();
+            let mask_lit = mask_lit(*len, span);
+            let offset = offset.to_tokens(span);
+            let field_bits = quote_spanned!(span=> (#bits_ident >> #offset) & #mask_lit);
+
+            match field_kind(ty) {
+                FieldKind::Bool => quote_spanned!(span=> #ident: #field_bits != 0),
+                FieldKind::Int => {
+                    let value = sign_extend_field(quote_spanned!(span=> (#field_bits) as #ty), *len, ty);
+                    quote_spanned!(span=> #ident: #value)
+                }
+                FieldKind::Enum => quote_spanned! {span=>
+                    #ident: match #ty::try_from_bits((#field_bits) as _) {
+                        ::core::option::Option::Some(value) => value,
+                        ::core::option::Option::None => #invalid,
+                    }
+                },
+            }
+        }).collect()
+    };
+    // the width of an enum field is given twice, so the attribute must agree with the enum
+    let enum_checks: Vec<_> = input.fields().iter().zip(&lens)
+        .filter(|(field, _)| matches!(field_kind(&field.ty), FieldKind::Enum))
+        .map(|(field, len)| {
+            let ty = &field.ty;
+            let err = format!("the field `{}` takes up {} bits, which differs from the `BITS` of `{}`",
+                field.ident, len, quote!(#ty));
+            quote_spanned!(field.len.span()=> const _: () = ::core::assert!(<#ty>::BITS as usize == #len, #err);)
+        })
+        .collect();
+
+    let unpack_fields = unpacked_fields(quote!(::core::panic!("bits are no valid enum variant")));
+    let try_unpack_fields = unpacked_fields(quote!(return ::core::result::Result::Err(#bits_ident)));
+
+    let accessors: Vec<_> = input.fields().iter().zip(&lens)
+        .filter(|(field, _)| !field.ident.to_string().starts_with('_'))
+        .map(|(field, len)| {
+            let ident = &field.ident;
+            let ty = &field.ty;
+            let vis = &field.vis;
+            let span = ident.span();
+            let setter = Ident::new(&format!("set_{}", ident), span);
+            let value = match field_kind(ty) {
+                FieldKind::Int if is_signed(ty) => sign_extend_field(quote!(value), *len, ty),
+                FieldKind::Int => {
+                    let mask_lit = mask_lit(*len, span);
+                    quote!(value & #mask_lit)
+                }
+                _ => quote!(value),
+            };
+
+            quote_spanned! {span=>
+                #vis fn #ident(&self) -> #ty {
+                    self.#ident
+                }
+
+                #vis fn #setter(&mut self, value: #ty) {
+                    self.#ident = #value;
+                }
+            }
+        })
+        .collect();
+
+    quote! {
+        #item
+
+        #(#enum_checks)*
+
+        #[allow(dead_code, clippy::unnecessary_cast, clippy::identity_op)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The number of bits occupied by all fields.
+            pub const BITS: u32 = #bit_len_lit;
+
+            /// Packs the fields into a single integer.
+            pub fn pack(&self) -> #backing {
+                #pack
+            }
+
+            /// Unpacks the fields from a single integer, ignoring all bits beyond `BITS`.
+            ///
+            /// Panics if the bits of an enum field are no valid variant.
+            pub fn unpack(#bits_ident: #backing) -> Self {
+                Self {
+                    #(#unpack_fields,)*
+                }
+            }
+
+            #(#accessors)*
+        }
+
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for #backing #where_clause {
+            fn from(value: #name #ty_generics) -> Self {
+                value.pack()
+            }
+        }
+
+        #[allow(clippy::unnecessary_cast, clippy::identity_op)]
+        impl #impl_generics ::core::convert::TryFrom<#backing> for #name #ty_generics #where_clause {
+            type Error = #backing;
+
+            fn try_from(#bits_ident: #backing) -> ::core::result::Result<Self, Self::Error> {
+                if #bits_ident.checked_shr(Self::BITS).unwrap_or(0) != 0 {
+                    return ::core::result::Result::Err(#bits_ident);
+                }
+                ::core::result::Result::Ok(Self {
+                    #(#try_unpack_fields,)*
+                })
+            }
+        }
+    }.into()
+}
+
+/// Sign-extends the lower `len` bits of a `value` of a signed field type `ty`, so negative values round-trip.
+///
+/// Values of unsigned types are returned as they are.
+fn sign_extend_field(value: TokenStream2, len: usize, ty: &Type) -> TokenStream2 {
+    match primitive_bits(ty) {
+        Some(ty_len) if is_signed(ty) && len < ty_len => {
+            let shift = LitInt::new(&(ty_len - len).to_string(), proc_macro2::Span::call_site());
+            quote!(((#value) << #shift) >> #shift)
+        }
+        _ => value,
+    }
+}
+
+/// Processes the `bseq_unpack` input stream.
+///
+/// If `fallible` is set, the fields are wrapped in an `Option` that is `None` on a mismatch,
+/// otherwise a mismatch panics.
+fn process_unpack(input: BitSeqUnpackInput, fallible: bool) -> TokenStream {
+    let value = input.value();
+    let value_ident = Ident::new("__bseq_value", proc_macro2::Span::mixed_site());
+
+    let pattern = input.pattern();
+    let condition = pattern_condition(pattern, &value_ident);
+    let bindings = pattern_bindings(pattern, &value_ident, |_| false);
+    let idents = pattern_idents(pattern);
+
+    let fields = if idents.len() == 1 {
+        quote!(#(#idents)*)
+    } else {
+        quote!((#(#idents),*))
+    };
+
+    let check = width_check(pattern, pattern.segments());
+    let value_check = value_check(pattern, &value_ident);
+    let unpacked = if fallible {
+        quote!({
+            #check
+            let #value_ident = #value;
+            #value_check
+            if #condition {
+                #(#bindings)*
+                ::core::option::Option::Some(#fields)
+            } else {
+                ::core::option::Option::None
+            }
+        })
+    } else {
+        quote!({
+            #check
+            let #value_ident = #value;
+            #value_check
+            if !(#condition) {
+                ::core::panic!("value does not match the bit sequence pattern");
+            }
+            #(#bindings)*
+            #fields
+        })
+    };
+
+    if input.is_let() {
+        quote!(let #fields = #unpacked;).into()
+    } else {
+        unpacked.into()
+    }
+}
+
+/// Processes the `bseq` input stream with a specified variable type.
+///
+/// `bseq!` has variable type None \
+/// `bseq8!` has variable type Option<Type<u8>> \
+/// ...
+fn process(input: TokenStream, var_type: Option<Type>) -> TokenStream {
+    // parse input
+    let input = parse_macro_input!(input as BitSeqInput);
+    let var_type = var_type.or_else(|| suffix_type(input.segments()));
+    expand(&input, &var_type).into()
+}
+
+/// Processes the input of the left-justified sized macros such as `bseq_32_left!`.
+fn process_left(input: TokenStream, var_type: Type) -> TokenStream {
+    let mut input = parse_macro_input!(input as BitSeqInput);
+    input.set_left_justified();
+    expand(&input, &Some(var_type)).into()
+}
+
+/// Processes the input of `bseq_as!`, whose integer type may be wrapped in `Wrapping` or `NonZero`.
+fn process_as(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqAsInput);
+    let ty = input.ty();
+    match target_wrapper(ty) {
+        Some((Wrapper::Wrapping, int_ty)) => {
+            let seq = expand(input.seq(), &Some(int_ty));
+            quote!(::core::num::Wrapping(#seq))
+        }
+        Some((Wrapper::NonZero, int_ty)) => {
+            let seq = expand(input.seq(), &Some(int_ty));
+            let value_ident = Ident::new("__bseq_value", proc_macro2::Span::mixed_site());
+            quote!(match <#ty>::new(#seq) {
+                ::core::option::Option::Some(#value_ident) => #value_ident,
+                ::core::option::Option::None => ::core::panic!("the bit sequence is zero"),
+            })
+        }
+        None => expand(input.seq(), &Some(ty.clone())),
+    }.into()
+}
+
+/// Processes the input of `bseq_lit!`, a sequence or an inclusive range of sequences.
+fn process_lit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as BitSeqLitInput);
+    let start = expand_lit(input.start());
+    match input.end() {
+        Some(end) => {
+            let end = expand_lit(end);
+            quote!(#start..=#end).into()
+        }
+        None => start.into(),
+    }
+}
+
+/// Expands a bit sequence, which must only consist of constant segments, into a single literal.
+fn expand_lit(input: &BitSeqInput) -> TokenStream2 {
+    let var_type = suffix_type(input.segments());
+    let segments = expand_fill(input.segments(), &var_type);
+
+    let mut offset = Offset::default();
+    let mut value: u128 = 0;
+    for seg in segments.iter().rev() {
+        let seg_offset = offset.fixed;
+        // mapping checks the segment, such as a literal that is too wide in strict mode
+        map_segment(seg, &mut offset, &var_type, input.is_strict());
+        let Some((seg_value, _)) = constant_value(seg) else {
+            abort!(seg.span(), "`bseq_lit!` only accepts segments whose value is known at expansion time, \
+                such as raw bits, hex values and literals with a width");
+        };
+        value |= seg_value.checked_shl(seg_offset as u32).unwrap_or(0);
+    }
+    // all widths are fixed, so these checks either fail right away or expand to nothing
+    width_check(input, &segments);
+    type_check(&segments, &var_type);
+
+    // spanned by the input, so a value that is too wide for the inferred type is reported by the compiler
+    let mut lit = typed_lit(value, &var_type);
+    if let Some(seg) = segments.first() {
+        lit.set_span(seg.span());
+    }
+    quote!(#lit)
+}
+
+/// Returns the type given by the integer suffixes of the segments, like `u16` in `bseq!(0xffu16 x:4)`.
+fn suffix_type(segments: &[BitSegment]) -> Option<Type> {
+    let mut suffix_ty: Option<Ident> = None;
+    for seg in segments {
+        let suffix = match seg {
+            Bits(bits) => bits.suffix().cloned(),
+            Expr(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }), _) if !lit.suffix().is_empty() => {
+                Some(Ident::new(lit.suffix(), lit.span()))
+            }
+            _ => None,
+        };
+
+        match (&suffix_ty, suffix) {
+            (Some(ty), Some(suffix)) if *ty != suffix => {
+                abort!(suffix, "the suffix `{}` conflicts with the suffix `{}` of a previous segment", suffix, ty)
+            }
+            (None, Some(suffix)) => suffix_ty = Some(suffix),
+            _ => {}
+        }
+    }
+    suffix_ty.map(|ty| parse_quote!(#ty))
+}
+
+/// Expands the bit sequence into the combined shift operations.
+fn expand(input: &BitSeqInput, var_type: &Option<Type>) -> TokenStream2 {
+    // signed sequences are built in the unsigned type of the same width, so masks and literals do not overflow
+    let signed_type = var_type.as_ref().filter(|ty| is_signed(ty));
+    let value_type = &match signed_type {
+        Some(ty) => primitive_bits(ty).and_then(unsigned_type),
+        None => var_type.clone(),
+    };
+
+    // segments are bound in the order they are written, which `@lsb;` reverses
+    let mut segments = expand_fill(input.segments(), var_type);
+    if input.is_lsb_first() {
+        reverse_segments(&mut segments);
+    }
+    let mut bindings = Vec::new();
+    let mut segments: Vec<_> = segments.into_iter()
+        .enumerate()
+        .map(|(index, seg)| bind_segment(seg, &index.to_string(), &mut bindings))
+        .collect();
+    if input.is_lsb_first() {
+        reverse_segments(&mut segments);
+    }
+
+    // construct shift token streams, where adjacent constant segments are folded into a single literal
+    let mut offset = Offset::default();
+    let mut shifts = Vec::new();
+    let mut run: Option<ConstRun> = None;
+    let typed = value_type.as_ref().and_then(primitive_bits).is_some();
+    for seg in segments.iter().rev() {
+        let seg_offset = offset.clone();
+        // constant segments are mapped as well, as mapping checks them
+        let shift = map_segment(seg, &mut offset, value_type, input.is_strict());
+        match constant_value(seg) {
+            Some((value, len)) => {
+                let run = run.get_or_insert_with(|| ConstRun::new(seg_offset));
+                run.push(value, len);
+                // without a type, the compiler checks the folded value of length expressions and hex values against
+                // the inferred type, and reports it at the most significant of them
+                if let (false, Expr(expr, _)) = (typed, seg) {
+                    run.span = literal_span(expr);
+                    // a zero adds no bits to the value, so its mask checks its width instead
+                    if value == 0 {
+                        run.widths.push(shift);
+                    }
+                }
+            }
+            None => {
+                shifts.extend(run.take().into_iter().flat_map(|run| run.into_shifts(value_type)));
+                shifts.push(shift);
+            }
+        }
+    }
+    let mut check = width_check(input, &segments);
+    check.extend(type_check(&segments, var_type));
+
+    // a signed value as the most significant segment extends its sign to the bits above the sequence
+    let ty_len = value_type.as_ref().and_then(primitive_bits);
+    let sign_extended = ty_len.is_some() && !input.is_left_justified()
+        && matches!(segments.first(), Some(Expr(_, SegmentLen::Signed(_))));
+
+    // combine all shift segments
+    let span = proc_macro2::Span::call_site();
+
+    if shifts.is_empty() {
+        let Some(mut run) = run else {
+            // if no input provided, result is 0
+            return quote_spanned!(span=> 0);
+        };
+        // a sequence of constant segments is a single literal, which can be used in patterns as well
+        if let Some(ty_len) = ty_len {
+            if input.is_left_justified() {
+                run.value = run.value.checked_shl(ty_len.saturating_sub(run.len) as u32).unwrap_or(0);
+            } else if sign_extended && (run.value >> (run.len - 1)) & 1 == 1 {
+                run.value |= u128::MAX.checked_shl(run.len as u32).unwrap_or(0) & (u128::MAX >> (128 - ty_len));
+            }
+        }
+        let seq = match signed_type {
+            Some(ty) => signed_lit(run.value, ty),
+            None => value_lit(run.value, value_type, run.span),
+        };
+        // the masked zeros above the literal keep their widths checked, though the sequence is no literal then
+        let seq = if run.widths.is_empty() {
+            seq
+        } else {
+            let shifts = run.into_shifts(value_type);
+            quote!(#(#shifts)|*)
+        };
+        return if check.is_empty() { seq } else { quote!({ #check #seq }) };
+    }
+    shifts.extend(run.into_iter().flat_map(|run| run.into_shifts(value_type)));
+
+    let seq = match value_type {
+        Some(ty) if input.is_left_justified() => left_justify(quote!((#(#shifts)|*) as #ty), &offset, ty),
+        Some(ty) if sign_extended => sign_extend(quote!((#(#shifts)|*) as #ty), &offset, ty),
+        Some(ty) => quote!((#(#shifts)|*) as #ty),
+        None => quote!(#(#shifts)|*),
+    };
+    let seq = match signed_type {
+        Some(ty) => quote!((#seq) as #ty),
+        None => seq,
+    };
+
+    if !has_runtime_len(&segments) {
+        return if bindings.is_empty() && check.is_empty() { seq } else { quote!({ #(#bindings)* #check #seq }) };
+    }
+
+    // the total width is only known at runtime, so it is checked against the type of the result
+    let result_ident = Ident::new("__bseq_result", proc_macro2::Span::mixed_site());
+    let bit_len = offset.to_tokens(span);
+    quote!({
+        #(#bindings)*
+        #check
+        let mut #result_ident = 0;
+        if #bit_len > ::core::mem::size_of_val(&#result_ident) * 8 {
+            ::core::panic!("the bit sequence is wider than its type");
+        }
+        #result_ident = #seq;
+        #result_ident
+    })
+}
+
+/// Adjacent segments whose values are known at expansion time, folded into a single value.
+struct ConstRun {
+    value: u128,
+    len: usize,
+    /// The offset of the least significant segment.
+    offset: Offset,
+    /// The span of the folded literal, which is only spanned by the input if the compiler should check its value.
+    span: proc_macro2::Span,
+    /// The masked zeros of length expressions and hex values above all set bits of an unsized sequence, which are
+    /// kept, as the literal does not carry their width.
+    widths: Vec<TokenStream2>,
+}
+
+impl ConstRun {
+    fn new(offset: Offset) -> Self {
+        ConstRun { value: 0, len: 0, offset, span: proc_macro2::Span::call_site(), widths: Vec::new() }
+    }
+
+    /// Adds the next more significant segment.
+    fn push(&mut self, value: u128, len: usize) {
+        self.value |= value.checked_shl(self.len as u32).unwrap_or(0);
+        self.len += len;
+        if value != 0 {
+            self.widths.clear();
+        }
+    }
+
+    /// Creates the literal shifted to the offset of the run, unless all its bits are `0`, followed by the masked
+    /// zeros above it.
+    fn into_shifts(self, expr_type: &Option<Type>) -> Vec<TokenStream2> {
+        let mut shifts = Vec::new();
+        if self.value != 0 {
+            shifts.push(self.literal(expr_type));
+        }
+        shifts.extend(self.widths);
+        shifts
+    }
+
+    /// Creates the literal shifted to the offset of the run.
+    fn literal(&self, expr_type: &Option<Type>) -> TokenStream2 {
+        let span = proc_macro2::Span::call_site();
+        if self.offset.consts.is_empty() {
+            // a value shifted out of 128 bits is only possible for types that are not checked, like aliases
+            let value = self.value.checked_shl(self.offset.fixed as u32).filter(|value| value >> self.offset.fixed == self.value);
+            if let Some(value) = value {
+                return value_lit(value, expr_type, self.span);
+            }
+        }
+        let lit = value_lit(self.value, expr_type, self.span);
+        let offset = self.offset.to_tokens(span);
+        quote!((#lit) << #offset)
+    }
+}
+
+/// Returns the value, truncated to its width, and the width of a segment that is known at expansion time,
+/// like `0110` or `!0:6`.
+fn constant_value(seg: &BitSegment) -> Option<(u128, usize)> {
+    let (value, len) = match seg {
+        Bits(bits) if !bits.has_dont_care() => (bits.value(), bits.len()),
+        Expr(expr, SegmentLen::Lit(len) | SegmentLen::Signed(len)) => {
+            (literal_value(expr, false)?, len.base10_parse().ok()?)
+        }
+        _ => return None,
+    };
+    Some((value & u128::MAX.checked_shr(128 - len as u32).unwrap_or(0), len))
+}
+
+/// Evaluates an integer literal, including unary operations on unsuffixed literals like `!0` or `-1`.
+///
+/// The operations are evaluated in two's complement, which truncates to the same bits in any type wide enough,
+/// but the operand of a suffixed literal like `!0u8` is only as wide as its suffix, so it is not evaluated.
+fn literal_value(expr: &syn::Expr, operand: bool) -> Option<u128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }) if !operand || lit.suffix().is_empty() => {
+            lit.base10_parse().ok()
+        }
+        syn::Expr::Paren(paren) => literal_value(&paren.expr, operand),
+        syn::Expr::Unary(unary) => {
+            let value = literal_value(&unary.expr, true)?;
+            match unary.op {
+                syn::UnOp::Not(_) => Some(!value),
+                syn::UnOp::Neg(_) => Some(value.wrapping_neg()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the span of the literal of a constant expression, like `0` in `!0`.
+fn literal_span(expr: &syn::Expr) -> proc_macro2::Span {
+    match expr {
+        syn::Expr::Paren(paren) => literal_span(&paren.expr),
+        syn::Expr::Unary(unary) => literal_span(&unary.expr),
+        expr => expr.span(),
+    }
+}
+
+/// Creates an integer literal of the type of the sequence, spanned by `span`.
+///
+/// The width of a type like an alias or `usize` is unknown to the macro, so the literal is a `u128` cast to the
+/// type, which truncates it like the masks of the other segments.
+fn value_lit(value: u128, expr_type: &Option<Type>, span: proc_macro2::Span) -> TokenStream2 {
+    match expr_type {
+        Some(ty) if primitive_bits(ty).is_none() => {
+            let lit = LitInt::new(&format!("{}u128", value), span);
+            quote!((#lit as #ty))
+        }
+        _ => {
+            let mut lit = typed_lit(value, expr_type);
+            lit.set_span(span);
+            quote!(#lit)
+        }
+    }
+}
+
+/// Creates an integer literal, which has the type of the sequence in sized macros.
+///
+/// The literal is not spanned by the input, so, as for the raw bits of an unsized sequence, a value that is
+/// too wide for the inferred type is truncated.
+fn typed_lit(value: u128, expr_type: &Option<Type>) -> LitInt {
+    let suffix = match expr_type {
+        Some(ty) if primitive_bits(ty).is_some() => quote!(#ty).to_string(),
+        _ => String::new(),
+    };
+    LitInt::new(&format!("{}{}", value, suffix), proc_macro2::Span::call_site())
+}
+
+/// Binds the value and the runtime length of a segment to temporaries, whose names end in `id`.
+///
+/// The `|`-chain evaluates the segments from the least significant one up, so binding them first evaluates each
+/// expression exactly once and in the order it is written. Literals have no side effects and are kept as they are.
+fn bind_segment(seg: BitSegment, id: &str, bindings: &mut Vec<TokenStream2>) -> BitSegment {
+    let (expr, len) = match seg {
+        Expr(expr, len) => (expr, len),
+        Repeat(group, count) => {
+            let group = group.into_iter()
+                .enumerate()
+                .map(|(index, seg)| bind_segment(seg, &format!("{}_{}", id, index), bindings))
+                .collect();
+            return Repeat(group, count);
+        }
+        seg => return seg,
+    };
+
+    let expr = bind_value(expr, &format!("__bseq_value_{}", id), bindings);
+
+    // runtime lengths are part of the offsets of all preceding segments as well
+    let len = match len {
+        SegmentLen::Runtime(len) => {
+            let len_ident = Ident::new(&format!("__bseq_len_{}", id), proc_macro2::Span::mixed_site());
+            bindings.push(quote_spanned!(len.span()=> let #len_ident: usize = #len;));
+            SegmentLen::Runtime(Box::new(parse_quote!(#len_ident)))
+        }
+        len => len,
+    };
+    Expr(expr, len)
+}
+
+/// Binds the value of a segment to a temporary named `ident` and returns the expression that replaces it.
+fn bind_value(expr: syn::Expr, ident: &str, bindings: &mut Vec<TokenStream2>) -> syn::Expr {
+    match expr {
+        syn::Expr::Lit(_) => expr,
+        // unary operations like `!x:4` are not checked in strict mode, so they keep their operator
+        syn::Expr::Unary(mut unary) => {
+            unary.expr = Box::new(bind_value(*unary.expr, ident, bindings));
+            syn::Expr::Unary(unary)
+        }
+        syn::Expr::Paren(paren) => bind_value(*paren.expr, ident, bindings),
+        expr => {
+            // the temporary has mixed-site hygiene, so it cannot capture variables of the caller, but the invisible
+            // group around it is spanned by the expression, so diagnostics and lints about the segment, such as an
+            // overflowing mask, still point to the expression
             let span = expr.span();
+            let ident = Ident::new(ident, proc_macro2::Span::mixed_site().located_at(span));
+            bindings.push(quote!(let #ident = #expr;));
+            syn::Expr::Group(syn::ExprGroup {
+                attrs: Vec::new(),
+                group_token: syn::token::Group(span),
+                expr: Box::new(parse_quote!(#ident)),
+            })
+        }
+    }
+}
 
-            let rep = if let Some(ty) = expr_type {
-                quote_spanned!(span=> (#expr as #ty) & #mask_lit)
-            } else {
-                quote_spanned!(span=> #expr & #mask_lit)
+/// Extends the sign of the most significant bit of the sequence, given its total width, to all bits of its type.
+fn sign_extend(seq: TokenStream2, bit_len: &Offset, ty: &Type) -> TokenStream2 {
+    let ty_len = primitive_bits(ty).expect("sign-extended sequences have a primitive type");
+    let signed = signed_type(ty_len).expect("primitive types have a signed counterpart");
+    let shift = if bit_len.consts.is_empty() {
+        match ty_len.saturating_sub(bit_len.fixed) {
+            0 => return seq,
+            shift => quote!(#shift),
+        }
+    } else {
+        // the signed value takes up at least one bit, so the shift does not overflow
+        let sum = bit_len.sum_tokens(proc_macro2::Span::call_site());
+        quote!((#ty_len - (#sum)))
+    };
+    // the arithmetic shift of the signed type copies the sign bit
+    quote!(((((#seq) << #shift) as #signed) >> #shift) as #ty)
+}
+
+/// Creates the literal of a signed type from the bits of its value, like `-1i8` for `0xff`.
+fn signed_lit(value: u128, ty: &Type) -> TokenStream2 {
+    let ty_len = primitive_bits(ty).expect("signed literals have a primitive type");
+    if (value >> (ty_len - 1)) & 1 == 0 {
+        let lit = LitInt::new(&format!("{}{}", value, quote!(#ty)), proc_macro2::Span::call_site());
+        return quote!(#lit);
+    }
+    let magnitude = value.wrapping_neg() & (u128::MAX >> (128 - ty_len));
+    let lit = LitInt::new(&format!("{}{}", magnitude, quote!(#ty)), proc_macro2::Span::call_site());
+    quote!(-#lit)
+}
+
+/// Shifts the sequence up against the most significant bit of its type, given the total width of the sequence.
+fn left_justify(seq: TokenStream2, bit_len: &Offset, ty: &Type) -> TokenStream2 {
+    let ty_len = primitive_bits(ty).expect("left-justified macros have a primitive type");
+    if bit_len.consts.is_empty() {
+        // a sequence that is too wide is reported when checking the type, and an empty one stays 0
+        return match ty_len.saturating_sub(bit_len.fixed) {
+            shift if shift == 0 || shift == ty_len => seq,
+            shift => quote!((#seq) << #shift),
+        };
+    }
+
+    // the shift is as wide as the type if all segments are empty, which would overflow
+    let sum = bit_len.sum_tokens(proc_macro2::Span::call_site());
+    quote!((#seq).checked_shl((#ty_len - (#sum)) as u32).unwrap_or(0))
+}
+
+/// Replaces the fill segment, if any, by a segment that takes up the bits left by all other segments.
+fn expand_fill(segments: &[BitSegment], var_type: &Option<Type>) -> Vec<BitSegment> {
+    let mut fills = segments.iter().filter(|seg| matches!(seg, Fill(_)));
+    let Some(Fill(fill)) = fills.next() else { return segments.to_vec() };
+    if let Some(Fill(other)) = fills.next() {
+        abort!(other, "only one fill segment is allowed in a bit sequence");
+    }
+
+    let Some(ty) = var_type else {
+        abort!(fill, "fill segments are only allowed in sized macros such as `bseq_32!`");
+    };
+    if has_runtime_len(segments) {
+        abort!(fill, "fill segments cannot be combined with lengths that are only known at runtime");
+    }
+
+    let mut bit_len = Offset::default();
+    for seg in segments.iter().filter(|seg| !matches!(seg, Fill(_))) {
+        bit_len.add(&segment_width(seg));
+    }
+
+    let span = fill.span();
+    let filled = match primitive_bits(ty) {
+        Some(ty_len) if bit_len.consts.is_empty() => {
+            // a sequence that is too wide is reported when checking the type
+            let len = ty_len.saturating_sub(bit_len.fixed);
+            (len > 0).then(|| Bits(BitString::new(fill.to_string().repeat(len), span)))
+        }
+        ty_len => {
+            // the width of a type like an alias or `usize` is only known to the compiler
+            let ty_len = match ty_len {
+                Some(ty_len) => quote!(#ty_len),
+                None => quote!((<#ty>::BITS as usize)),
             };
+            let sum = bit_len.sum_tokens(span);
+            let value = if fill.to_string() == "1" { parse_quote_spanned!(span=> !0) } else { parse_quote_spanned!(span=> 0) };
+            Some(Expr(value, SegmentLen::Const(parse_quote_spanned!(span=> { #ty_len - (#sum) }))))
+        }
+    };
+
+    segments.iter()
+        .filter_map(|seg| match seg {
+            Fill(_) => filled.clone(),
+            seg => Some(seg.clone()),
+        })
+        .collect()
+}
 
-            (rep, len)
+fn map_segment(seg: &BitSegment, curr_offset: &mut Offset, expr_type: &Option<Type>, strict: bool) -> TokenStream2 {
+    let width = segment_width(seg);
+    let span = seg.span();
+    let val = match seg {
+        Bits(bits) => {
+            if bits.has_dont_care() {
+                abort!(span, "don't-care bits are only allowed in patterns and `bseq_mask!`");
+            }
+            // sized macros give the literal their type, so it is not inferred as a narrower type
+            value_lit(bits.value(), expr_type, proc_macro2::Span::call_site())
+        }
+        Expr(expr, SegmentLen::Signed(_)) if strict => {
+            let Width::Fixed(len) = width else { unreachable!("signed values have a fixed width") };
+            strict_signed_segment(expr, len, expr_type)
+        }
+        // unary operations like `!0:6` are meant to be truncated, so they are never checked
+        Expr(expr, _) if strict && !matches!(expr, syn::Expr::Unary(_)) => strict_segment(expr, &width, expr_type),
+        Expr(expr, _) => {
+            let mask = value_mask(&width, span, expr_type);
+            let value = cast_expr(expr, expr_type);
+            quote_spanned!(span=> #value & #mask)
         }
+        Enum(path) => {
+            // the discriminants of a `BitEnum` always fit into its `BITS`
+            if let Some(ty) = expr_type {
+                quote_spanned!(span=> #path.to_bits() as #ty)
+            } else {
+                // the bits are converted to the inferred type of the sequence rather than fixing it to the type of
+                // the bits, and the literal makes the conversion infer the type like the other segments. The
+                // conversion is not spanned by the input, as converting to the same type is not a mistake of the caller
+                let bits = quote_spanned!(span=> #path.to_bits());
+                quote!(if false { 0 } else { ::core::convert::From::from(#bits) })
+            }
+        }
+        Fill(_) => unreachable!("fill segments are replaced before mapping"),
+        Repeat(group, count) => repeat_value(group, count, expr_type, strict),
     };
 
-    let span = val.span();
-    let bit_len_lit = LitInt::new(&curr_bit_len.to_string(), span);
-    let res = quote_spanned!(span=> (#val) << #bit_len_lit);
-    *curr_bit_len += len;
+    let offset = shift_tokens(curr_offset, &width, span);
+    let res = quote_spanned!(span=> (#val) << #offset);
+    curr_offset.add(&width);
     res
-}
\ No newline at end of file
+}
+
+/// Creates the value of segments repeated a constant number of times, like `[01 x:2]{N}`.
+///
+/// The segments are evaluated once and shifted in once per repetition, in a loop that can be evaluated in a `const`
+/// context as well.
+fn repeat_value(group: &[BitSegment], count: &syn::Block, expr_type: &Option<Type>, strict: bool) -> TokenStream2 {
+    let mut input = BitSeqInput::new(group.to_vec());
+    if strict {
+        input.set_strict();
+    }
+    let value = expand(&input, expr_type);
+
+    let span = proc_macro2::Span::call_site();
+    let mut group_len = Offset::default();
+    for seg in group {
+        group_len.add(&segment_width(seg));
+    }
+    // a signed most significant segment extends its sign beyond the segments, which would overlap other repetitions
+    let mask = if group_len.consts.is_empty() {
+        mask_tokens(&Width::Fixed(group_len.fixed), span)
+    } else {
+        mask_tokens(&Width::Const(group_len.to_tokens(span)), span)
+    };
+    let group_len = group_len.to_tokens(span);
+
+    let group_ident = Ident::new("__bseq_group", proc_macro2::Span::mixed_site());
+    let value_ident = Ident::new("__bseq_repeated", proc_macro2::Span::mixed_site());
+    let index_ident = Ident::new("__bseq_index", proc_macro2::Span::mixed_site());
+    // the first repetition is not shifted, as the segments may take up all bits of the type
+    quote!({
+        let #group_ident = (#value) & #mask;
+        let mut #value_ident = 0;
+        let mut #index_ident: usize = 0;
+        while #index_ident < #count {
+            #value_ident = if #index_ident == 0 { #group_ident } else { (#value_ident << #group_len) | #group_ident };
+            #index_ident += 1;
+        }
+        #value_ident
+    })
+}
+
+/// Casts the value of a length expression to the type of the sequence, if any.
+///
+/// Literals whose suffix already is the type are not cast.
+fn cast_expr(expr: &syn::Expr, expr_type: &Option<Type>) -> TokenStream2 {
+    let span = expr.span();
+    match (expr, expr_type) {
+        (syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }), Some(ty)) if lit.suffix() == quote!(#ty).to_string() => {
+            quote_spanned!(span=> #expr)
+        }
+        // not spanned by the expression, as casting to the same type is not a mistake of the caller
+        (_, Some(ty)) => quote!((#expr as #ty)),
+        (_, None) => quote_spanned!(span=> #expr),
+    }
+}
+
+/// Maps the value of a length expression in strict mode, where it must fit into its width.
+///
+/// Integer literals with a fixed width are checked at expansion time, all other values by a `debug_assert!`.
+fn strict_segment(expr: &syn::Expr, width: &Width, expr_type: &Option<Type>) -> TokenStream2 {
+    let span = expr.span();
+    let mask = value_mask(width, span, expr_type);
+
+    if let (syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }), Width::Fixed(len)) = (expr, width) {
+        let value: u128 = lit.base10_parse().unwrap_or_else(|_| abort!(lit, "Couldn't be parsed!"));
+        let value_len = (u128::BITS - value.leading_zeros()) as usize;
+        if value_len > *len {
+            abort!(lit, "the literal takes up {} bits, but its width is only {} bits", value_len, len);
+        }
+        let value = cast_expr(expr, expr_type);
+        return quote_spanned!(span=> #value & #mask);
+    }
+
+    let value_ident = Ident::new("__bseq_segment", proc_macro2::Span::mixed_site());
+    let fits = match expr_type {
+        Some(ty) => {
+            // not spanned by the expression, as converting to the same type is not a mistake of the caller
+            let cast_ident = Ident::new("__bseq_cast", proc_macro2::Span::mixed_site());
+            quote! {
+                ::core::matches!(
+                    <#ty as ::core::convert::TryFrom<_>>::try_from(#value_ident),
+                    ::core::result::Result::Ok(#cast_ident) if #cast_ident & #mask == #cast_ident
+                )
+            }
+        }
+        None => quote_spanned!(span=> #value_ident & #mask == #value_ident),
+    };
+    assert_segment(expr, &value_ident, fits, &mask, expr_type)
+}
+
+/// Maps a signed value in strict mode, where it must be within the signed range of its width, like `-8..=7`
+/// for `imm:s4`.
+///
+/// Integer literals, including negated ones, are checked at expansion time, all other values by a `debug_assert!`.
+fn strict_signed_segment(expr: &syn::Expr, len: usize, expr_type: &Option<Type>) -> TokenStream2 {
+    let span = expr.span();
+    let mask = value_mask(&Width::Fixed(len), span, expr_type);
+    let (min, max) = (i128::MIN >> (128 - len), i128::MAX >> (128 - len));
+
+    if let Some(value) = literal_value(expr, false) {
+        // the bits of the value in two's complement
+        let value = value as i128;
+        if !(min..=max).contains(&value) {
+            abort!(expr, "the literal is outside of the range {}..={} of a signed {}-bit value", min, max, len);
+        }
+        let value = cast_expr(expr, expr_type);
+        return quote_spanned!(span=> #value & #mask);
+    }
+
+    let value_ident = Ident::new("__bseq_segment", proc_macro2::Span::mixed_site());
+    let cast_ident = Ident::new("__bseq_cast", proc_macro2::Span::mixed_site());
+    let (min, max) = (proc_macro2::Literal::i128_suffixed(min), proc_macro2::Literal::i128_suffixed(max));
+    let fits = quote! {
+        ::core::matches!(
+            <i128 as ::core::convert::TryFrom<_>>::try_from(#value_ident),
+            ::core::result::Result::Ok(#cast_ident) if (#min..=#max).contains(&#cast_ident)
+        )
+    };
+    assert_segment(expr, &value_ident, fits, &mask, expr_type)
+}
+
+/// Binds the value of a segment to `value_ident` and asserts that it `fits`, before it is masked.
+fn assert_segment(
+    expr: &syn::Expr,
+    value_ident: &Ident,
+    fits: TokenStream2,
+    mask: &TokenStream2,
+    expr_type: &Option<Type>,
+) -> TokenStream2 {
+    // the value is bound once, as it is used by both the assertion and the sequence
+    let span = expr.span();
+    let masked = match expr_type {
+        Some(ty) => quote_spanned!(span=> (#value_ident as #ty) & #mask),
+        None => quote_spanned!(span=> #value_ident & #mask),
+    };
+
+    quote_spanned!(span=> {
+        let #value_ident = #expr;
+        ::core::debug_assert!(#fits, "the value of a segment does not fit into its width");
+        #masked
+    })
+}
+
+/// The number of bits a segment takes up in the sequence.
+enum Width {
+    /// A width known at expansion time.
+    Fixed(usize),
+    /// A width given by a `usize` expression, which is evaluated by the generated code.
+    Const(TokenStream2),
+}
+
+/// The position of a segment's least significant bit in the sequence.
+///
+/// It is the sum of the widths of all less significant segments, split into the part known at expansion time
+/// and the constant expressions evaluated by the compiler.
+#[derive(Clone, Default)]
+struct Offset {
+    fixed: usize,
+    consts: Vec<TokenStream2>,
+}
+
+impl Offset {
+    fn add(&mut self, width: &Width) {
+        match width {
+            Width::Fixed(len) => self.fixed += len,
+            Width::Const(len) => self.consts.push(len.clone()),
+        }
+    }
+
+    fn to_tokens(&self, span: proc_macro2::Span) -> TokenStream2 {
+        let sum = self.sum_tokens(span);
+        if self.consts.is_empty() {
+            sum
+        } else {
+            quote_spanned!(span=> (#sum))
+        }
+    }
+
+    /// Creates the sum of all widths without surrounding parentheses.
+    fn sum_tokens(&self, span: proc_macro2::Span) -> TokenStream2 {
+        let fixed = LitInt::new(&self.fixed.to_string(), span);
+        let consts = &self.consts;
+        quote_spanned!(span=> #fixed #(+ #consts)*)
+    }
+}
+
+/// Returns the width of the segment.
+fn segment_width(seg: &BitSegment) -> Width {
+    match seg {
+        Bits(bits) => Width::Fixed(bits.len()),
+        Expr(_, SegmentLen::Lit(len_lit) | SegmentLen::Signed(len_lit)) => {
+            let len = len_lit.base10_parse().unwrap_or_else(|_| abort!(len_lit, "Couldn't be parsed!"));
+            if len > 128 {
+                abort!(len_lit, "the width of a segment is {} bits, but at most 128 bits are supported", len);
+            }
+            Width::Fixed(len)
+        }
+        Expr(_, SegmentLen::Const(block)) => {
+            // an inline const forces evaluation at compile time, which also works for const generics
+            let span = block.span();
+            let len_ident = Ident::new("__bseq_len", proc_macro2::Span::mixed_site());
+            Width::Const(quote_spanned!(span=> const { let #len_ident: usize = #block; #len_ident }))
+        }
+        Expr(_, SegmentLen::Runtime(len)) => Width::Const(quote_spanned!(len.span()=> (#len))),
+        Enum(path) => {
+            // the enum type is the variant path without the variant
+            let mut ty = path.path.clone();
+            ty.segments.pop();
+            ty.segments.pop_punct();
+            let span = path.span();
+            Width::Const(quote_spanned!(span=> (#ty::BITS as usize)))
+        }
+        Fill(fill) => abort!(fill, "fill segments are only allowed in sized macros such as `bseq_32!`"),
+        Repeat(group, count) => {
+            let span = count.span();
+            let mut group_len = Offset::default();
+            for seg in group {
+                group_len.add(&segment_width(seg));
+            }
+            let sum = group_len.sum_tokens(span);
+            let len_ident = Ident::new("__bseq_len", proc_macro2::Span::mixed_site());
+            Width::Const(quote_spanned!(span=> const { let #len_ident: usize = #count * (#sum); #len_ident }))
+        }
+    }
+}
+
+/// Creates a literal with the `len` least significant bits set.
+fn mask_lit(len: usize, span: proc_macro2::Span) -> LitInt {
+    let mask = u128::MAX.checked_shr((128 - len) as u32).unwrap_or(0);
+    LitInt::new(&mask.to_string(), span)
+}
+
+/// Creates an expression with the `width` least significant bits set.
+///
+/// For constant widths, the mask is computed without overflowing the type even if the width covers all of its bits.
+fn mask_tokens(width: &Width, span: proc_macro2::Span) -> TokenStream2 {
+    match width {
+        Width::Fixed(len) => {
+            let mask_lit = mask_lit(*len, span);
+            quote_spanned!(span=> #mask_lit)
+        }
+        Width::Const(len) => quote_spanned!(span=> (if #len == 0 { 0 } else { !(!0 << (#len - 1) << 1) })),
+    }
+}
+
+/// Creates the mask of a segment of the given `width` in the type of the sequence.
+///
+/// The mask of a full-width segment would overflow a signed alias like `type Imm = i16`, so for types of unknown
+/// width, the mask is computed from `!0` like for constant widths.
+fn value_mask(width: &Width, span: proc_macro2::Span, expr_type: &Option<Type>) -> TokenStream2 {
+    match (width, expr_type) {
+        (Width::Fixed(0), _) => mask_tokens(width, span),
+        (Width::Fixed(len), Some(ty)) if primitive_bits(ty).is_none() => {
+            let shift = LitInt::new(&(len - 1).to_string(), span);
+            quote_spanned!(span=> !(!0 << #shift << 1))
+        }
+        _ => mask_tokens(width, span),
+    }
+}
+
+/// Creates the amount a segment of the given `width` is shifted by to reach its `offset`.
+///
+/// An empty segment may start right after the most significant bit of the type,
+/// so it is not shifted at all to avoid an overflow.
+fn shift_tokens(offset: &Offset, width: &Width, span: proc_macro2::Span) -> TokenStream2 {
+    let is_zero = offset.fixed == 0 && offset.consts.is_empty();
+    match width {
+        Width::Fixed(0) => quote_spanned!(span=> 0),
+        Width::Fixed(_) => offset.to_tokens(span),
+        Width::Const(_) if is_zero => offset.to_tokens(span),
+        Width::Const(len) => {
+            let offset = offset.sum_tokens(span);
+            quote_spanned!(span=> (if #len == 0 { 0 } else { #offset }))
+        }
+    }
+}
+
+/// Checks that the segments take up exactly the width declared with `@N;`, if any.
+///
+/// Widths known at expansion time are checked right away, others by a constant assertion or,
+/// if any length is only known at runtime, by an assertion at runtime.
+fn width_check(input: &BitSeqInput, segments: &[BitSegment]) -> TokenStream2 {
+    let Some(width_lit) = input.width() else { return quote!() };
+    let width: usize = width_lit.base10_parse().unwrap_or_else(|_| abort!(width_lit, "Couldn't be parsed!"));
+
+    let mut bit_len = Offset::default();
+    for seg in segments {
+        bit_len.add(&segment_width(seg));
+    }
+
+    if bit_len.consts.is_empty() {
+        if bit_len.fixed != width {
+            abort!(width_lit, "the segments take up {} bits, but the sequence is declared to be {} bits wide",
+                bit_len.fixed, width);
+        }
+        return quote!();
+    }
+
+    let span = width_lit.span();
+    let bit_len = bit_len.to_tokens(span);
+    let err = format!("the segments do not take up the declared {} bits", width);
+    if has_runtime_len(segments) {
+        quote_spanned!(span=> ::core::assert!(#bit_len == #width_lit, #err);)
+    } else {
+        quote_spanned!(span=> const { ::core::assert!(#bit_len == #width_lit, #err) };)
+    }
+}
+
+/// Checks that the segments fit into the primitive type `ty` of a sized macro, or into 128 bits without a type.
+///
+/// Widths known at expansion time are checked right away, reporting the segment that exceeds the type,
+/// and constant widths by a constant assertion. Runtime lengths are checked when evaluating the sequence.
+fn type_check(segments: &[BitSegment], ty: &Option<Type>) -> TokenStream2 {
+    let ty_len = match ty {
+        Some(ty) => match primitive_bits(ty) {
+            Some(ty_len) => ty_len,
+            None => return bits_check(segments, ty),
+        },
+        None => 128,
+    };
+
+    let mut bit_len = Offset::default();
+    let mut exceeding = None;
+    for seg in segments.iter().rev() {
+        bit_len.add(&segment_width(seg));
+        if exceeding.is_none() && bit_len.fixed > ty_len {
+            exceeding = Some(seg.span());
+        }
+    }
+
+    if let Some(span) = exceeding {
+        let at_least = if bit_len.consts.is_empty() { "" } else { "at least " };
+        match ty {
+            Some(ty) => abort!(span, "the bit sequence takes up {}{} bits, but `{}` only has {} bits",
+                at_least, bit_len.fixed, quote!(#ty), ty_len),
+            None => abort!(span, "the bit sequence takes up {}{} bits, but at most 128 bits are supported",
+                at_least, bit_len.fixed),
+        }
+    }
+
+    // without a type, constant widths are checked by the compiler when shifting
+    let Some(ty) = ty else { return quote!() };
+    if bit_len.consts.is_empty() || has_runtime_len(segments) {
+        return quote!();
+    }
+
+    let span = proc_macro2::Span::call_site();
+    let bit_len = bit_len.to_tokens(span);
+    let err = format!("the bit sequence is wider than `{}`", quote!(#ty));
+    quote_spanned!(span=> const { ::core::assert!(#bit_len <= #ty_len, #err) };)
+}
+
+/// Checks that the segments fit into a type whose width is unknown to the macro, like an alias or `usize`,
+/// by a constant assertion against its `BITS`.
+fn bits_check(segments: &[BitSegment], ty: &Type) -> TokenStream2 {
+    // runtime lengths are checked when evaluating the sequence
+    if has_runtime_len(segments) {
+        return quote!();
+    }
+
+    let mut bit_len = Offset::default();
+    for seg in segments {
+        bit_len.add(&segment_width(seg));
+    }
+    let span = proc_macro2::Span::call_site();
+    let bit_len = bit_len.to_tokens(span);
+    let err = format!("the bit sequence is wider than `{}`", quote!(#ty));
+    quote_spanned!(span=> const { ::core::assert!(#bit_len <= <#ty>::BITS as usize, #err) };)
+}
+
+/// Checks that a pattern fits into the matched `value`, whose type is only known to the compiler.
+///
+/// A pattern that is too wide would shift the value by more than its width, so it panics instead,
+/// which fails to compile in a `const` context.
+fn value_check(pattern: &BitSeqInput, value: &Ident) -> TokenStream2 {
+    let mut bit_len = Offset::default();
+    for seg in pattern.segments() {
+        bit_len.add(&segment_width(seg));
+    }
+    if bit_len.fixed == 0 && bit_len.consts.is_empty() {
+        return quote!();
+    }
+
+    let span = proc_macro2::Span::call_site();
+    let bit_len = bit_len.to_tokens(span);
+    // `size_of_val` is not `const` in all supported versions
+    let bits_ident = Ident::new("__bseq_bits", proc_macro2::Span::mixed_site());
+    quote_spanned!(span=> {
+        const fn #bits_ident<T>(_: &T) -> usize {
+            ::core::mem::size_of::<T>() * 8
+        }
+        ::core::assert!(#bit_len <= #bits_ident(&#value), "the bit sequence pattern is wider than the matched value");
+    })
+}
+
+/// Returns whether any segment has a length that is only known at runtime.
+fn has_runtime_len(segments: &[BitSegment]) -> bool {
+    segments.iter().any(|seg| matches!(seg, Expr(_, SegmentLen::Runtime(_))))
+}
+
+/// Computes the offset of each segment, counted from the least significant bit.
+fn segment_offsets(segments: &[BitSegment]) -> Vec<Offset> {
+    let mut bit_len = Offset::default();
+    let mut offsets: Vec<_> = segments.iter().rev()
+        .map(|seg| {
+            let offset = bit_len.clone();
+            bit_len.add(&segment_width(seg));
+            offset
+        })
+        .collect();
+    offsets.reverse();
+    offsets
+}
+
+/// Returns the identifier a length expression binds to in a pattern, if any.
+fn binding_ident(expr: &syn::Expr) -> Option<&Ident> {
+    match expr {
+        syn::Expr::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+/// Builds the condition that checks all fixed segments of the pattern against `value`.
+fn pattern_condition(pattern: &BitSeqInput, value: &Ident) -> TokenStream2 {
+    let segments = pattern.segments();
+    let checks: Vec<_> = segments.iter()
+        .zip(segment_offsets(segments))
+        .filter_map(|(seg, offset)| {
+            let span = seg.span();
+            let width = segment_width(seg);
+            let mask = mask_tokens(&width, span);
+            let offset = shift_tokens(&offset, &width, span);
+            let field = quote_spanned!(span=> ((#value >> #offset) & #mask));
+
+            match seg {
+                Bits(bits) if bits.care_mask() == 0 => None,
+                Bits(bits) => {
+                    let care_lit = LitInt::new(&bits.care_mask().to_string(), span);
+                    let num_lit = LitInt::new(&bits.value().to_string(), span);
+                    Some(quote_spanned!(span=> ((#value >> #offset) & #care_lit) == #num_lit))
+                }
+                Expr(expr, _) if binding_ident(expr).is_some() => None,
+                Expr(expr, _) => Some(quote_spanned!(span=> #field == ((#expr) & #mask))),
+                Enum(path) => Some(quote_spanned!(span=> #field == #path.to_bits().into())),
+                Fill(_) => unreachable!("fill segments have no width in patterns"),
+                Repeat(group, count) => {
+                    let binding = group.iter().find_map(|seg| match seg {
+                        Expr(expr, _) => binding_ident(expr),
+                        _ => None,
+                    });
+                    if let Some(ident) = binding {
+                        abort!(ident, "fields cannot be bound in segments repeated a constant number of times");
+                    }
+                    let value = repeat_value(group, count, &None, false);
+                    Some(quote_spanned!(span=> #field == #value))
+                }
+            }
+        })
+        .collect();
+
+    if checks.is_empty() {
+        quote!(true)
+    } else {
+        quote!(#(#checks)&&*)
+    }
+}
+
+/// Returns the identifiers of all fields the pattern binds, in the order they are written.
+fn pattern_idents(pattern: &BitSeqInput) -> Vec<&Ident> {
+    let mut idents: Vec<_> = pattern.segments().iter()
+        .filter_map(|seg| match seg {
+            Expr(expr, _) => binding_ident(expr),
+            _ => None,
+        })
+        .collect();
+    // the segments of `@lsb;` are kept in layout order, which is the reverse of the written order
+    if pattern.is_lsb_first() {
+        idents.reverse();
+    }
+    idents
+}
+
+/// Builds the `let` statements that bind the fields of the pattern extracted from `value`.
+///
+/// Fields for which `used_elsewhere` returns true, like those used in the guard of a `bseq_match!` arm,
+/// are not reported as unused.
+fn pattern_bindings(pattern: &BitSeqInput, value: &Ident, used_elsewhere: impl Fn(&Ident) -> bool) -> Vec<TokenStream2> {
+    let segments = pattern.segments();
+    segments.iter()
+        .zip(segment_offsets(segments))
+        .filter_map(|(seg, offset)| {
+            let Expr(expr, _) = seg else { return None };
+            let ident = binding_ident(expr)?;
+            let span = ident.span();
+            let width = segment_width(seg);
+            let mask = mask_tokens(&width, span);
+            let offset = shift_tokens(&offset, &width, span);
+            let allow = if used_elsewhere(ident) { quote!(#[allow(unused_variables)]) } else { quote!() };
+            Some(quote_spanned!(span=> #allow let #ident = (#value >> #offset) & #mask;))
+        })
+        .collect()
+}
+
+/// Returns whether the tokens contain `ident`, including within groups like the arguments of a macro call.
+fn mentions_ident(tokens: TokenStream2, ident: &Ident) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        proc_macro2::TokenTree::Ident(other) => other == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Returns the number of bits of a primitive integer type, or `None` for any other type.
+fn primitive_bits(ty: &Type) -> Option<usize> {
+    let Type::Path(path) = ty else { return None };
+    let ident = path.path.get_ident()?.to_string();
+    match ident.as_str() {
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        "u64" | "i64" => Some(64),
+        "u128" | "i128" => Some(128),
+        _ => None,
+    }
+}
+
+/// Returns the smallest unsigned integer type with at least `bit_len` bits.
+fn unsigned_type(bit_len: usize) -> Option<Type> {
+    match bit_len {
+        0..=8 => Some(parse_quote!(u8)),
+        9..=16 => Some(parse_quote!(u16)),
+        17..=32 => Some(parse_quote!(u32)),
+        33..=64 => Some(parse_quote!(u64)),
+        65..=128 => Some(parse_quote!(u128)),
+        _ => None,
+    }
+}
+
+/// Returns the signed integer type with `bit_len` bits, or `None` if there is no such type.
+fn signed_type(bit_len: usize) -> Option<Type> {
+    match bit_len {
+        8 => Some(parse_quote!(i8)),
+        16 => Some(parse_quote!(i16)),
+        32 => Some(parse_quote!(i32)),
+        64 => Some(parse_quote!(i64)),
+        128 => Some(parse_quote!(i128)),
+        _ => None,
+    }
+}
+
+/// Returns whether the type is a signed primitive integer type.
+fn is_signed(ty: &Type) -> bool {
+    primitive_bits(ty).is_some() && quote!(#ty).to_string().starts_with('i')
+}
+
+/// A wrapper around an integer type, which `bseq_as!` accepts as the type of a sequence.
+enum Wrapper {
+    Wrapping,
+    NonZero,
+}
+
+/// Splits a type like `Wrapping<u16>`, `NonZero<u32>` or `NonZeroU32` into its wrapper and the integer type.
+fn target_wrapper(ty: &Type) -> Option<(Wrapper, Type)> {
+    let Type::Path(path) = ty else { return None };
+    let last = path.path.segments.last()?;
+    let name = last.ident.to_string();
+    if let Some(int_ty) = name.strip_prefix("NonZero").filter(|int_ty| !int_ty.is_empty()) {
+        let int_ty = Ident::new(&int_ty.to_lowercase(), last.ident.span());
+        return Some((Wrapper::NonZero, parse_quote!(#int_ty)));
+    }
+
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+    let Some(syn::GenericArgument::Type(int_ty)) = args.args.first() else { return None };
+    match name.as_str() {
+        "Wrapping" => Some((Wrapper::Wrapping, int_ty.clone())),
+        "NonZero" => Some((Wrapper::NonZero, int_ty.clone())),
+        _ => None,
+    }
+}
+
+/// How a field of a `#[bit_seq]` struct is converted from and to its bits.
+enum FieldKind {
+    Bool,
+    /// A primitive integer, converted with `as`.
+    Int,
+    /// Any other type, which is expected to derive `BitEnum`.
+    Enum,
+}
+
+fn field_kind(ty: &Type) -> FieldKind {
+    match ty {
+        Type::Path(path) if path.path.is_ident("bool") => FieldKind::Bool,
+        Type::Path(path) if path.path.is_ident("usize") || path.path.is_ident("isize") => FieldKind::Int,
+        _ if primitive_bits(ty).is_some() => FieldKind::Int,
+        _ => FieldKind::Enum,
+    }
+}
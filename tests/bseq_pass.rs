@@ -1,4 +1,8 @@
-use bit_seq::{bseq, bseq_128, bseq_16, bseq_32, bseq_64, bseq_8};
+// the expanded bit manipulations carry the spans of the macro input, and the expected
+// values are grouped like the segments they are compared to
+#![allow(clippy::identity_op, clippy::erasing_op, clippy::zero_prefixed_literal, clippy::unusual_byte_groupings)]
+
+use bit_seq::{bit_seq, BitEnum, bseq, bseq_as, bseq_128, bseq_16, bseq_32, bseq_64, bseq_8, bseq_128_left, bseq_16_left, bseq_32_left, bseq_64_left, bseq_8_left, bseq_i128, bseq_i16, bseq_i32, bseq_i64, bseq_i8, bseq_lit, bseq_mask, bseq_match, bseq_matches, bseq_strict, bseq_try_unpack, bseq_unpack};
 
 #[test]
 fn test_bseq_bits() {
@@ -156,4 +160,873 @@ fn test_bseq_unary() {
     assert_eq!(bseq!(-1:8), 0xff);
     let var = 1;
     assert_eq!(bseq!(-var:8), 0xff);
-}
\ No newline at end of file
+}
+#[test]
+fn test_bseq_match() {
+    fn decode(word: u32) -> (u32, u32, u32) {
+        bseq_match!(word {
+            0110 rd:5 imm:12 => (1, rd, imm),
+            0111 rd:5 rs:5 _pad:7 if rd == rs => (2, rd, rs),
+            0111 rd:5 rs:5 _pad:7 => (3, rd, rs),
+            0x1 1:1 rest:15 => (4, rest, 0),
+            _ => (0, 0, 0),
+        })
+    }
+
+    assert_eq!(decode(bseq!(0110 3:5 5:12)), (1, 3, 5));
+    assert_eq!(decode(bseq!(0111 3:5 3:5 0:7)), (2, 3, 3));
+    assert_eq!(decode(bseq!(0111 3:5 4:5 0:7)), (3, 3, 4));
+    assert_eq!(decode(bseq!(0001 1 0x7fff:15)), (4, 0x7fff, 0));
+    assert_eq!(decode(bseq!(0001 0 0x7fff:15)), (0, 0, 0));
+    assert_eq!(decode(bseq!(1111 0:17)), (0, 0, 0));
+
+    let word: u8 = 0b111_01;
+    let t = bseq_match!(word {
+        !0:3 low:2 => low,
+        _ => 0,
+    });
+    assert_eq!(t, 1);
+
+    // a field used only in the guard or only in the body is not unused
+    #[deny(unused_variables)]
+    fn imm_of_r0(word: u32) -> Option<u32> {
+        bseq_match!(word {
+            0110 rd:5 imm:12 if rd == 0 => Some(imm),
+            _ => None,
+        })
+    }
+    assert_eq!(imm_of_r0(bseq!(0110 0:5 5:12)), Some(5));
+    assert_eq!(imm_of_r0(bseq!(0110 3:5 5:12)), None);
+}
+
+#[test]
+fn test_bseq_unpack() {
+    let word: u32 = bseq!(01 5:3 3:5 200:8);
+    let (op, rd, imm) = bseq_unpack!(word => 01 op:3 rd:5 imm:8);
+    assert_eq!((op, rd, imm), (5, 3, 200));
+
+    bseq_unpack!(let word => 0x1 op:3 rd:5 imm:8);
+    assert_eq!((op, rd, imm), (5, 3, 200));
+
+    let imm = bseq_unpack!(word => 01 0x5:3 3:5 imm:8);
+    assert_eq!(imm, 200);
+
+    const RD: u8 = bseq_unpack!(0b10_000011u8 => 10 rd:6);
+    assert_eq!(RD, 3);
+
+    assert_eq!(bseq_try_unpack!(word => 01 op:3 rd:13), Some((5, bseq!(3:5 200:8))));
+    assert_eq!(bseq_try_unpack!(word => 11 op:3 rd:13), None);
+}
+
+#[test]
+#[should_panic(expected = "value does not match the bit sequence pattern")]
+fn test_bseq_unpack_mismatch() {
+    let word: u8 = 0b11_000000;
+    let _rd = bseq_unpack!(word => 10 rd:6);
+}
+
+#[test]
+#[should_panic(expected = "the bit sequence pattern is wider than the matched value")]
+fn test_bseq_match_too_wide() {
+    let word: u8 = 0x12;
+    let _low = bseq_match!(word {
+        0x1 low:8 => low,
+        _ => 0,
+    });
+}
+
+#[bit_seq]
+#[derive(Debug, PartialEq)]
+struct Ctrl {
+    #[bits(3)]
+    mode: u8,
+    #[bits(1)]
+    en: bool,
+    #[bits(4)]
+    _reserved: u8,
+}
+
+#[bit_seq(u32)]
+#[derive(Debug, PartialEq)]
+pub struct Instr {
+    #[bits(4)]
+    pub op: u8,
+    #[bits(5)]
+    pub rd: u16,
+    #[bits(12)]
+    pub imm: u32,
+}
+
+#[test]
+fn test_bit_seq_struct() {
+    let mut ctrl = Ctrl { mode: 5, en: true, _reserved: 0 };
+    assert_eq!(Ctrl::BITS, 8);
+    let (mode, en) = (ctrl.mode, ctrl.en);
+    assert_eq!(ctrl.pack(), bseq_8!(mode:3 en:1 0:4));
+    assert_eq!(ctrl.pack(), 0b101_1_0000);
+    assert_eq!(Ctrl::unpack(0b101_1_0000), ctrl);
+
+    ctrl.set_mode(0xff);
+    ctrl.set_en(false);
+    assert_eq!((ctrl.mode(), ctrl.en()), (0b111, false));
+    assert_eq!(u8::from(ctrl), 0b111_0_0000);
+
+    let instr = Instr { op: 0b0110, rd: 3, imm: 5 };
+    let raw: u32 = instr.into();
+    assert_eq!(Instr::BITS, 21);
+    assert_eq!(raw, bseq!(0110 3:5 5:12));
+    assert_eq!(Instr::try_from(raw), Ok(Instr { op: 0b0110, rd: 3, imm: 5 }));
+    assert_eq!(Instr::try_from(raw | 1 << 21), Err(raw | 1 << 21));
+    assert_eq!(Instr::unpack(raw | 1 << 21), Instr { op: 0b0110, rd: 3, imm: 5 });
+}
+
+#[bit_seq]
+#[derive(Debug, PartialEq)]
+struct Offsets {
+    #[bits(4)]
+    a: i8,
+    #[bits(12)]
+    b: i16,
+    #[bits(8)]
+    c: i8,
+}
+
+#[test]
+fn test_bit_seq_struct_signed() {
+    let mut offsets = Offsets { a: -1, b: -100, c: -128 };
+    assert_eq!(offsets.pack(), bseq!(0xf 0xf9c 0x80));
+    assert_eq!(Offsets::unpack(offsets.pack()), offsets);
+    assert_eq!(Offsets::unpack(bseq!(0111 0x800 0x7f)), Offsets { a: 7, b: -2048, c: 127 });
+
+    offsets.set_a(-1);
+    assert_eq!(offsets.a(), -1);
+    offsets.set_a(0b1000);
+    assert_eq!(offsets.a(), -8);
+    offsets.set_b(0x7ff);
+    assert_eq!(offsets.b(), 0x7ff);
+}
+
+#[derive(BitEnum, Clone, Copy, Debug, PartialEq)]
+#[bits(2)]
+enum Mode {
+    Slow,
+    Normal = 2,
+    Fast,
+}
+
+#[bit_seq]
+#[derive(Debug, PartialEq)]
+struct ModeCtrl {
+    #[bits(1)]
+    en: bool,
+    #[bits(2)]
+    mode: Mode,
+    #[bits(5)]
+    count: u8,
+}
+
+#[test]
+fn test_bit_enum() {
+    assert_eq!(Mode::BITS, 2);
+    assert_eq!(Mode::Fast.to_bits(), 3);
+    assert_eq!(Mode::try_from_bits(2), Some(Mode::Normal));
+    assert_eq!(Mode::try_from_bits(1), None);
+
+    assert_eq!(bseq!(Mode::Fast), 0b11);
+    // the bits of a variant do not fix the type of an unsized sequence to the type of the bits
+    assert_eq!(bseq!(Mode::Fast 0:8), 0b11_0000_0000);
+    let t: u32 = bseq!(Mode::Normal 0:30);
+    assert_eq!(t, 0b10 << 30);
+    assert_eq!(bseq_8!(1 Mode::Normal 0x1:4), 0b1_10_0001);
+    let count = 3;
+    assert_eq!(bseq_32!(Mode::Normal count:5 Mode::Slow 1), 0b10_00011_00_1);
+
+    // a variant is followed by unary and parenthesized segments rather than continued by them
+    let a = 1;
+    assert_eq!(bseq_8!(Mode::Fast -a:3 000), 0b11_111_000);
+    assert_eq!(bseq_8!(Mode::Fast !a:3 000), 0b11_110_000);
+    assert_eq!(bseq_8!(Mode::Fast (a):3 000), 0b11_001_000);
+    assert_eq!(bseq_8!(Mode::Fast (a + 1):3 000), 0b11_010_000);
+    assert_eq!(bseq_8!(Mode::Slow [1 0]{3}), 0b00_10_10_10);
+
+    let word: u16 = bseq_16!(Mode::Normal 0x0f);
+    let m = bseq_match!(word {
+        Mode::Fast _rest:8 => 0,
+        Mode::Normal rest:8 => rest,
+        _ => 1,
+    });
+    assert_eq!(m, 0x0f);
+
+    let ctrl = ModeCtrl { en: true, mode: Mode::Normal, count: 3 };
+    assert_eq!(ctrl.pack(), bseq_8!(1 Mode::Normal 3:5));
+    assert_eq!(ModeCtrl::unpack(ctrl.pack()), ctrl);
+    assert_eq!(ctrl.mode(), Mode::Normal);
+    assert_eq!(ModeCtrl::try_from(0b1_01_00011), Err(0b1_01_00011));
+}
+
+#[test]
+fn test_bseq_dont_care() {
+    assert_eq!(bseq_mask!(01xx 1x0), (0b1100_101, 0b0100_100));
+    assert_eq!(bseq_mask!(xx 1), (0b001, 0b001));
+    assert_eq!(bseq_mask!(x01 0x1), (0b011_1111, 0b001_0001));
+    let var = 2;
+    let (mask, value): (u8, u8) = bseq_mask!(var:2 x 1x);
+    assert_eq!((mask, value), (0b11_0_10, 0b10_0_10));
+
+    assert!(bseq_matches!(0b0110_100u8, 01xx 1x0));
+    assert!(bseq_matches!(0b0101_110u8, 01xx 1x0));
+    assert!(!bseq_matches!(0b0101_111u8, 01xx 1x0));
+    assert!(bseq_matches!(0xf0u8, xxxx 0000));
+    assert!(bseq_matches!(0xf0u8, 1x high:2 0x0 if high == 3));
+    assert!(!bseq_matches!(0xf0u8, 1x high:2 0x0 if high == 2));
+
+    let word: u16 = 0b1010_0000_0000_0111;
+    let t = bseq_match!(word {
+        0 xxx _rest:12 => 0,
+        1x1x xxxx low:8 => low,
+        _ => 1,
+    });
+    assert_eq!(t, 7);
+    assert_eq!(bseq_unpack!(word => 1xx0 x:4 low:8), (0, 7));
+}
+
+struct Config {
+    flags: u8,
+    regs: [u32; 2],
+}
+
+impl Config {
+    fn mode(&self) -> u8 {
+        self.flags >> 4
+    }
+}
+
+fn double(v: u8) -> u8 {
+    v * 2
+}
+
+#[test]
+fn test_bseq_expr_segments() {
+    let cfg = Config { flags: 0x25, regs: [0b10, 0b111] };
+    assert_eq!(bseq!(cfg.flags:4 1), 0b0101_1);
+    assert_eq!(bseq!(cfg.mode():2 0), 0b10_0);
+    assert_eq!(bseq_8!(cfg.regs[1]:2 cfg.regs[0]:2), 0b11_10);
+
+    let (a, b) = (3u8, 4u8);
+    assert_eq!(bseq!((a + b):4 1), 0b0111_1);
+    assert_eq!(bseq!(1 (a * b):3), 0b1_100);
+    assert_eq!(bseq!(double(a):3), 0b110);
+    assert_eq!(bseq_16!(cfg.regs[1] as u8:3 -(a as i8):4), 0b111_1101);
+    assert_eq!(bseq!(!cfg.flags:3), 0b010);
+
+    let word: u8 = bseq!(cfg.flags:4 0110);
+    assert_eq!(bseq_unpack!(word => cfg.flags:4 low:4), 0b0110);
+}
+
+mod isa {
+    pub const OP_LOAD: u32 = 0b0000011;
+
+    pub mod ext {
+        pub const OP_FENCE: u32 = 0b0001111;
+    }
+}
+
+struct Reg;
+
+impl Reg {
+    const ZERO: u32 = 0;
+    const RA: u32 = 1;
+}
+
+#[test]
+fn test_bseq_path_segments() {
+    let rd = 5;
+    assert_eq!(bseq!(isa::OP_LOAD:7 rd:5), 0b0000011_00101);
+    assert_eq!(bseq!(isa::ext::OP_FENCE:7 Reg::ZERO:5), 0b0001111_00000);
+    assert_eq!(bseq!(Reg::RA:5 1), 0b00001_1);
+    assert_eq!(bseq!(crate::isa::OP_LOAD:3), 0b011);
+    assert_eq!(bseq!(Mode::Normal as u8:2 Mode::Fast), 0b10_11);
+    assert_eq!(bseq_16!(u8::MAX:4 0), 0b1111_0);
+
+    let word = bseq!(isa::OP_LOAD:7 rd:5);
+    assert!(bseq_matches!(word, isa::OP_LOAD:7 x:5));
+    assert!(!bseq_matches!(word, isa::ext::OP_FENCE:7 x:5));
+}
+
+const OPCODE_LEN: usize = 7;
+
+fn encode<const W: usize>(x: u32) -> u32 {
+    bseq!(1 x:{W})
+}
+
+fn encode_double<const N: usize>(x: u32, y: u32) -> u32 {
+    bseq!(x:{N * 2} y:{N} 0)
+}
+
+#[test]
+fn test_bseq_const_len() {
+    assert_eq!(encode::<3>(0b1111), 0b1_111);
+    assert_eq!(encode::<0>(0b1111), 0b1);
+    assert_eq!(encode::<31>(u32::MAX), u32::MAX);
+    assert_eq!(encode_double::<2>(0b11011, 0b101), 0b1011_01_0);
+
+    let op = 0b0000011;
+    assert_eq!(bseq!(op:{OPCODE_LEN} 101), 0b0000011_101);
+    assert_eq!(bseq_8!(op:{OPCODE_LEN + 1}), 0b11);
+    assert_eq!(bseq!(op:{OPCODE_LEN} Mode::Fast 1), 0b0000011_11_1);
+
+    let word = bseq!(op:{OPCODE_LEN} 101);
+    assert!(bseq_matches!(word, 0000011 x:{3}));
+    let (opcode, funct) = bseq_unpack!(word => opcode:{OPCODE_LEN} funct:3);
+    assert_eq!((opcode, funct), (0b0000011, 0b101));
+}
+
+#[test]
+fn test_bseq_runtime_len() {
+    let payload = 0b10110u32;
+    for len in 0..=5 {
+        let word = bseq!(1 payload:(len) 01);
+        let expected = (1 << (len + 2)) | ((payload & ((1 << len) - 1)) << 2) | 0b01;
+        assert_eq!(word, expected);
+    }
+
+    let (n, m) = (3, 2);
+    assert_eq!(bseq_16!(0xf:(n) 0:(m + 1) 1), 0b111_000_1);
+    assert_eq!(bseq_32!(payload:(n) Mode::Fast), 0b110_11);
+    assert_eq!(bseq_8!(payload:(0) 0xff:(8)), 0xff);
+    assert_eq!(bseq_8!(payload:{0} 0xff:8), 0xff);
+    assert_eq!(bseq_mask!(payload:{0} 0xff:8 xx), (0x3fc, 0x3fc));
+
+    let (hi, lo) = bseq_unpack!(0b101_0110u32 => hi:(n) lo:4);
+    assert_eq!((hi, lo), (0b101, 0b0110));
+}
+
+#[test]
+#[should_panic(expected = "the bit sequence is wider than its type")]
+fn test_bseq_runtime_len_overflow() {
+    let len = std::hint::black_box(8);
+    bseq_8!(1 0xff:(len));
+}
+
+fn encode_itype<const W: usize>(imm: u32) -> u32 {
+    bseq!(@32; 0110 imm:{W} 0:{28 - W})
+}
+
+#[test]
+fn test_bseq_exact_width() {
+    let (rd, imm) = (0b10101, 0x1fffff);
+    assert_eq!(bseq!(@32; 0110 rd:5 imm:21 11), 0b0110_10101_111111111111111111111_11);
+    assert_eq!(bseq_16!(@16; 0xabcd), 0xabcd);
+    assert_eq!(bseq!(@0;), 0);
+    assert_eq!(bseq!(@4; 10 Mode::Fast), 0b10_11);
+    assert_eq!(encode_itype::<20>(0xfffff), 0b0110_11111111111111111111_00000000);
+
+    let len = 4;
+    assert_eq!(bseq!(@8; 1111 0:(len)), 0b1111_0000);
+    assert_eq!(bseq_unpack!(0b1010u8 => @4; hi:2 lo:2), (0b10, 0b10));
+    assert_eq!(bseq_mask!(@4; 1x x0), (0b1001, 0b1000));
+}
+
+#[test]
+#[should_panic(expected = "the segments do not take up the declared 8 bits")]
+fn test_bseq_exact_width_runtime() {
+    let len = 3;
+    bseq!(@8; 1111 0:(len));
+}
+
+#[test]
+fn test_bseq_sized_full_width() {
+    assert_eq!(bseq_8!(0xf 1111), 0xff);
+    assert_eq!(bseq_16!(0xff:8 0xff), 0xffff);
+    assert_eq!(bseq_32!(1 0:{31}), 1 << 31);
+    assert_eq!(bseq_64!(Mode::Fast 0:62), 0b11 << 62);
+}
+
+#[test]
+fn test_bseq_strict() {
+    let (rd, imm) = (0b10101u32, 0x7ffu32);
+    assert_eq!(bseq_strict!(0110 rd:5 imm:11), 0b0110_10101_11111111111);
+    assert_eq!(bseq_strict!(3:2 0xf:4 !0:3 -1:2), 0b11_1111_111_11);
+    assert_eq!(bseq_strict!(Mode::Fast 101), 0b11_101);
+    assert_eq!(bseq_8!(@strict; rd:5 0:3), 0b10101_000);
+    assert_eq!(bseq_16!(@strict; @16; imm:16), 0x7ff);
+
+    let len = 4;
+    assert_eq!(bseq_strict!(rd:(len + 1) 0:{2}), 0b10101_00);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "the value of a segment does not fit into its width"))]
+fn test_bseq_strict_truncated() {
+    let rd = 0b100000;
+    assert_eq!(bseq_strict!(rd:5), 0);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "the value of a segment does not fit into its width"))]
+fn test_bseq_strict_sized_truncated() {
+    let rd = 256u32;
+    assert_eq!(bseq_8!(@strict; rd:8), 0);
+}
+
+#[test]
+fn test_bseq_128_full_width() {
+    let max = u128::MAX;
+    assert_eq!(bseq_128!(max:128), u128::MAX);
+    assert_eq!(bseq!(max:128), u128::MAX);
+    assert_eq!(bseq_128!(!0:128), u128::MAX);
+    assert_eq!(bseq_128!(0xffffffffffffffffffffffffffffffff), u128::MAX);
+    assert_eq!(bseq_128!(11111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111), u128::MAX);
+    assert_eq!(bseq_128!(10000000000000000000000000000000000000000000000000000000000000000), 1 << 64);
+    assert_eq!(bseq_128!(1 0:127), 1 << 127);
+    assert_eq!(bseq_128!(max:{128}), u128::MAX);
+    assert_eq!(bseq_128!(@strict; max:128), u128::MAX);
+
+    let (mask, value) = bseq_mask!(x1111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111);
+    assert_eq!((mask, value), (u128::MAX >> 1, u128::MAX >> 1));
+    assert!(bseq_matches!(max, 11111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111));
+    assert_eq!(bseq_unpack!(max => hi:64 lo:64), (u64::MAX as u128, u64::MAX as u128));
+}
+
+#[test]
+fn test_bseq_suffix() {
+    let x: u8 = 0b1010;
+    let y: u32 = 0b11;
+
+    // the suffix is not part of the width
+    assert_eq!(bseq!(0x0fu8), 0x0f);
+    assert_eq!(bseq!(0xffu64 1), 0b11111111_1);
+    assert_eq!(bseq!(1 0x0u16), 0b1_0000);
+
+    // the suffix sets the type of the whole sequence
+    let t = bseq!(0xffu64 x:8 y:2);
+    let _: u64 = t;
+    assert_eq!(t, 0b11111111_00001010_11);
+    assert_eq!(bseq!(101u16 x:4 y:2), 0b101_1010_11);
+    assert_eq!(bseq!(x:4 0b11u32:2 y:2), 0b1010_11_11);
+    assert_eq!(bseq!(1u8 0u8 x:2), 0b1_0_10);
+    assert_eq!(bseq_16!(0xffu8 x:8), 0xff0a);
+
+    // as well as in strict mode and of the mask and value of `bseq_mask!`
+    let t = bseq_strict!(0xffu64 x:8);
+    let _: u64 = t;
+    assert_eq!(t, 0xff0a);
+    let (mask, value) = bseq_mask!(0xffu64 xxxx xxxx x:8 0:16);
+    let _: (u64, u64) = (mask, value);
+    assert_eq!((mask, value), (0xff_00_ff_ffff, 0xff_00_0a_0000));
+
+    assert!(bseq_matches!(0b1010u8, 1x1xu8));
+}
+
+#[test]
+fn test_bseq_literal_formats() {
+    // digit separators
+    assert_eq!(bseq!(0110_1010), 0b0110_1010);
+    assert_eq!(bseq!(1 0xff_ff), 0b1_11111111_11111111);
+    assert_eq!(bseq!(01_10u16 1), 0b0110_1);
+
+    // a `0b` prefix keeps leading zeros
+    assert_eq!(bseq!(1 0b0010), 0b1_0010);
+    assert_eq!(bseq!(0b0000_0001 0b1), 0b00000001_1);
+    assert_eq!(bseq!(0b11u8), 0b11);
+
+    // octal digits take up 3 bits each
+    assert_eq!(bseq!(1 0o17), 0b1_001_111);
+    assert_eq!(bseq!(0o7_7 0), 0b111_111_0);
+    assert_eq!(bseq!(0o0u16 1), 0b000_1);
+
+    assert!(bseq_matches!(0b1010_0110u8, 0b1010 01_x0));
+    assert_eq!(bseq_mask!(0b1x_x0), (0b1001, 0b1000));
+}
+
+#[test]
+fn test_bseq_sized_literal() {
+    assert_eq!(bseq!(4'b1010), 0b1010);
+    assert_eq!(bseq!(8'hFF 4'b0001), 0xff_1);
+    assert_eq!(bseq!(6'd42), 0b101010);
+    assert_eq!(bseq!(1 8'h0f), 0b1_00001111);
+    assert_eq!(bseq!(8'o17 1), 0b00001111_1);
+    assert_eq!(bseq!(16'hFF_FF), 0xffff);
+    assert_eq!(bseq!(8'HfF), 0xff);
+    assert_eq!(bseq!(4'd0 1), 0b0000_1);
+
+    let rd = 3;
+    assert_eq!(bseq_16!(7'b0110011 rd:5 4'h0), 0b0110011_00011_0000);
+
+    assert!(bseq_matches!(0b1010_0110u8, 4'b1x1x 4'hx));
+    assert_eq!(bseq_mask!(8'bx1), (0b1, 0b1));
+    assert_eq!(bseq_mask!(4'b1x), (0b1110, 0b0010));
+}
+
+#[test]
+fn test_bseq_repetition() {
+    assert_eq!(bseq!(1{8}), 0xff);
+    assert_eq!(bseq!(0 1{3} 0), 0b0_111_0);
+    assert_eq!(bseq!(10{2} 0xf{2}), 0b1010_11111111);
+    assert_eq!(bseq!(1{0} 1), 0b1);
+
+    let x = 0b10;
+    assert_eq!(bseq!([01 x:2]{4}), 0b0110_0110_0110_0110);
+    assert_eq!(bseq!(1 [0 x:1]{2} [11]), 0b1_00_00_11);
+    assert_eq!(bseq!([1 [0]{2}]{2}), 0b100_100);
+    assert_eq!(bseq!(x:3{2}), 0b010_010);
+    assert_eq!(bseq!(x:{2}{3}), 0b10_10_10);
+    assert_eq!(bseq!(Mode::Fast{2} 0), 0b11_11_0);
+    assert_eq!(bseq!(@16; 4'hA{4}), 0xaaaa);
+
+    assert!(bseq_matches!(0xf0u8, 1{4} x{4}));
+    assert_eq!(bseq_mask!([1x]{2}), (0b1010, 0b1010));
+
+    const EIGHT: usize = 8;
+    assert_eq!(bseq!(1{EIGHT}), 0xff);
+    assert_eq!(bseq_16!(1{EIGHT} 0{EIGHT / 2} 1{4}), 0xff_0f);
+    assert_eq!(low_ones::<5>(), 0b11111);
+    assert_eq!(low_ones::<32>(), u32::MAX);
+
+    // groups and other segments can be repeated a constant number of times as well
+    const FOUR: usize = 4;
+    assert_eq!(bseq!([01 x:2]{FOUR}), 0b0110_0110_0110_0110);
+    assert_eq!(bseq!(1 x:3{FOUR / 2} 0x3), 0b1_010_010_0011);
+    assert_eq!(bseq!(Mode::Fast{FOUR - 2} [1 0{FOUR}]{1}), 0b11_11_10000);
+    assert_eq!(bseq_16!([0xa x:2]{2} ..1), 0b1010_10_1010_10_1111);
+    assert_eq!(bseq_i8!([1 0]{FOUR}), 0b1010_1010u8 as i8);
+    assert_eq!(bseq!(@lsb; 1 [01 x:2]{FOUR - 2}), bseq!(@lsb; 1 [01 x:2]{2}));
+    assert_eq!(bseq!(@lsb; 1 [01 x:2]{FOUR - 2}), 0b10_01_10_01_1);
+    assert_eq!(repeated::<3>(0b101), 0b0_101_0_101_0_101);
+    assert_eq!(repeated::<0>(0b101), 0);
+    assert_eq!(repeated::<8>(0b1111), 0x7777_7777);
+    let y = -1;
+    assert_eq!(bseq_16!([y:s4]{FOUR / 2} 0:8), 0xff00);
+    const ALTERNATING: u16 = bseq_16!([10]{FOUR * 2});
+    assert_eq!(ALTERNATING, 0xaaaa);
+
+    assert!(bseq_matches!(0b10_10_10_1u8, [1 0]{FOUR - 1} 1));
+    assert!(!bseq_matches!(0b10_11_10_1u8, [1 0]{FOUR - 1} 1));
+    let word: u16 = bseq_16!([01 x:2]{FOUR});
+    assert_eq!(bseq_unpack!(word => [01 0x2:2]{FOUR - 1} low:4), 0b0110);
+
+    // the segments are evaluated once, in the order they are written
+    let mut calls = Vec::new();
+    let mut next = |value: u16| {
+        calls.push(value);
+        value
+    };
+    assert_eq!(bseq!(next(1):1 [next(2):2 1]{FOUR}), 0b1_101_101_101_101);
+    assert_eq!(calls, [1, 2]);
+}
+
+fn low_ones<const W: usize>() -> u32 {
+    bseq_32!(0{32 - W} 1{W})
+}
+
+fn repeated<const N: usize>(x: u32) -> u32 {
+    bseq_32!([0 x:3]{N})
+}
+
+fn top_field<const W: usize>(x: u32) -> u32 {
+    bseq_32!(x:{W} ..0)
+}
+
+#[test]
+fn test_bseq_fill() {
+    assert_eq!(bseq_32!(1101 ..0), 0b1101 << 28);
+    assert_eq!(bseq_8!(..1 00), 0b111111_00);
+    assert_eq!(bseq_16!(1 ..0 1), 0b1000_0000_0000_0001);
+    assert_eq!(bseq_8!(0xff ..0), 0xff);
+    assert_eq!(bseq_128!(..1), u128::MAX);
+    assert_eq!(bseq!(1u16 ..0), 1 << 15);
+    assert_eq!(bseq_8!(Mode::Fast ..0 1), 0b11_00000_1);
+    assert_eq!(top_field::<4>(0xf), 0xf << 28);
+
+    let rd = 0b101;
+    assert_eq!(bseq_16!(@16; ..1 rd:3), 0b1111111111111_101);
+}
+
+fn left_field<const W: usize>(x: u16) -> u16 {
+    bseq_16_left!(1 x:{W})
+}
+
+#[test]
+fn test_bseq_left() {
+    assert_eq!(bseq_8_left!(101), 0b101_00000);
+    assert_eq!(bseq_8_left!(0xff), 0xff);
+    assert_eq!(bseq_16_left!(0xab 0x5), 0xab50);
+    assert_eq!(bseq_32_left!(0110 ..1), 0b0110 << 28 | 0x0fff_ffff);
+    assert_eq!(bseq_64_left!(0xdead), 0xdead << 48);
+    assert_eq!(bseq_128_left!(1 0), 1 << 127);
+    assert_eq!(bseq_8_left!(Mode::Fast), 0b11_000000);
+    assert_eq!(bseq_8_left!(), 0);
+
+    let rd = 0b00101;
+    assert_eq!(bseq_32_left!(@strict; 0110 rd:5), 0b0110_00101 << 23);
+    assert_eq!(left_field::<3>(0b010), 0b1_010 << 12);
+    assert_eq!(left_field::<0>(0), 1 << 15);
+    assert_eq!(left_field::<15>(0), 1 << 15);
+
+    let len = 4;
+    assert_eq!(bseq_16_left!(1 rd:(len)), 0b1_0101 << 11);
+    let len = 0;
+    assert_eq!(bseq_16_left!(rd:(len)), 0);
+}
+
+#[test]
+fn test_bseq_lsb() {
+    let (bfinal, btype) = (1, 0b10);
+    assert_eq!(bseq!(@lsb; bfinal:1 btype:2), 0b10_1);
+    assert_eq!(bseq!(@lsb; 110 0xa 4'b1001), 0b1001_1010_110);
+    assert_eq!(bseq!(@lsb; 1 0{3} [01 1]{2}), 0b1_01_1_01_000_1);
+    assert_eq!(bseq!(@lsb; Mode::Slow 1), 0b1_00);
+    assert_eq!(bseq_8!(@lsb; 110 ..0), 0b00000_110);
+    assert_eq!(bseq_16!(@lsb; @strict; ..1 btype:2), 0b10_11111111111111);
+    assert_eq!(bseq!(@lsb; @8; 0xf btype:{4}), 0x2f);
+    assert_eq!(bseq_8_left!(@lsb; 1 0), 0b01_000000);
+
+    let len = 3;
+    assert_eq!(bseq!(@lsb; btype:(len) 1), 0b1_010);
+
+    assert_eq!(bseq_mask!(@lsb; 1 1x), (0b10_1, 0b10_1));
+    bseq_unpack!(let 0b10_1u8 => @lsb; lo:1 hi:2);
+    assert_eq!((lo, hi), (1, 0b10));
+    let (lo, hi) = bseq_unpack!(0b10_1u8 => @lsb; lo:1 hi:2);
+    assert_eq!((lo, hi), (1, 0b10));
+    assert_eq!(bseq_try_unpack!(0b10_1u8 => @lsb; lo:1 hi:2), Some((1, 0b10)));
+
+    let word: u8 = 0b0110_1_001;
+    let fields = bseq_match!(word {
+        @lsb; 001 flag:1 op:4 => (flag, op),
+        _ => (0, 0),
+    });
+    assert_eq!(fields, (1, 0b0110));
+    assert!(bseq_matches!(word, @lsb; 1 x{2} flag:1 x{4} if flag == 1));
+    assert!(bseq_matches!(word, @8; 0110 1 001));
+}
+
+#[test]
+fn test_bseq_signed() {
+    assert_eq!(bseq_i8!(1 0:7), i8::MIN);
+    assert_eq!(bseq_i8!(0111 1111), i8::MAX);
+    assert_eq!(bseq_i16!(0xffff), -1);
+    assert_eq!(bseq_i32!(1 ..0), i32::MIN);
+    assert_eq!(bseq_i64!(..1), -1);
+    assert_eq!(bseq_i128!(0 ..1), i128::MAX);
+
+    let x: u8 = 0x80;
+    assert_eq!(bseq_i8!(x:8), -128);
+}
+
+#[test]
+fn test_bseq_signed_segment() {
+    let imm = -100;
+    assert_eq!(bseq_i16!(imm:s12), -100);
+    assert_eq!(bseq_i32!(imm:s12 0:5), -100 << 5);
+    assert_eq!(bseq_32!(imm:s12 0:5), (-100i32 << 5) as u32);
+    assert_eq!(bseq_i64!(imm:s8 1 0), -100 << 2 | 0b10);
+    assert_eq!(bseq_i8!(imm:s8), -100);
+
+    // positive values and values that are not the most significant segment are not extended
+    let pos = 100;
+    assert_eq!(bseq_i32!(pos:s12), 100);
+    assert_eq!(bseq_i32!(0 imm:s12), 0xf9c);
+    assert_eq!(bseq!(imm:s12), 0xf9c);
+    assert_eq!(bseq_8_left!(imm:s4), 0xc0);
+
+    // constant segments fold into a negative literal
+    assert_eq!(bseq_i32!(-3:s4 0xf), -3 << 4 | 0xf);
+    assert_eq!(bseq_32!(-1:s1 0), u32::MAX - 1);
+
+    // the most significant segment of an lsb-first sequence is the last one
+    assert_eq!(bseq_i16!(@lsb; 01 imm:s8), -100 << 2 | 0b01);
+
+    const W: usize = 12;
+    assert_eq!(bseq_i32!(imm:s12 0:{W}), -100 << 12);
+    let len = 12;
+    assert_eq!(bseq_i32!(imm:s12 0:(len)), -100 << 12);
+}
+
+#[test]
+fn test_bseq_signed_strict() {
+    let (min, max) = (-2048, 2047);
+    assert_eq!(bseq_i16!(@strict; min:s12), -2048);
+    assert_eq!(bseq_i16!(@strict; max:s12), 2047);
+    assert_eq!(bseq_strict!(-8:s4 0000), 0x80);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "the value of a segment does not fit into its width"))]
+fn test_bseq_signed_strict_out_of_range() {
+    let imm = std::hint::black_box(2048);
+    bseq_i16!(@strict; imm:s12);
+}
+
+type RegWord = u32;
+
+#[test]
+fn test_bseq_as() {
+    let rd: u8 = 3;
+    let t = bseq_as!(RegWord; 0110 rd:5);
+    assert_eq!(t, 0b0110_00011);
+    assert_eq!(bseq_as!(RegWord; 0110), 0b0110u32);
+    assert_eq!(bseq_as!(u16; 0xff rd:8), 0xff03);
+    assert_eq!(bseq_as!(i16; ..1), -1);
+    assert_eq!(bseq_as!(i32; rd:s2 0:4), -1 << 4);
+    assert_eq!(bseq_as!(usize; 1 rd:4), 0b1_0011);
+    assert_eq!(bseq_as!(u64; @strict; @16; 0xff 0:8), 0xff00);
+
+    // the intermediate casts to the type of the sequence keep the bits shifted beyond the type of the value
+    assert_eq!(bseq_as!(RegWord; rd:8 0:24), 0x0300_0000);
+
+    // full-width segments do not overflow aliases of signed types
+    type Imm = i16;
+    let x: i16 = -1;
+    assert_eq!(bseq_as!(Imm; x:16), -1);
+    assert_eq!(bseq_as!(Imm; 0xffff), -1);
+    assert_eq!(bseq_as!(Imm; 0x7f 1{8}), i16::MAX);
+    assert_eq!(bseq_as!(Imm; 1 ..0), i16::MIN);
+    assert_eq!(bseq_as!(Imm; @strict; 0x7 rd:12), 0x7003);
+
+    assert_eq!(bseq_as!(usize; ..1), usize::MAX);
+    assert_eq!(bseq_as!(isize; 0 ..1), isize::MAX);
+    assert_eq!(bseq_as!(RegWord; 0110 ..0 rd:4), 0x6000_0003);
+}
+
+#[test]
+fn test_bseq_as_wrapped() {
+    use std::num::{NonZero, NonZeroU16, NonZeroUsize, Wrapping};
+
+    let x = 0xa;
+    assert_eq!(bseq_as!(Wrapping<u8>; x:4 ..1), Wrapping(0xaf));
+    assert_eq!(bseq_as!(Wrapping<u8>; ..1) + Wrapping(1), Wrapping(0));
+    assert_eq!(bseq_as!(std::num::Wrapping<i16>; x:s4 0:4), Wrapping(-6 << 4));
+    assert_eq!(bseq_as!(NonZero<u32>; 1 x:4).get(), 0b1_1010);
+    assert_eq!(bseq_as!(NonZeroU16; 0xff ..0).get(), 0xff00);
+    assert_eq!(bseq_as!(NonZeroUsize; x:4).get(), 0xa);
+
+    const ONE: NonZero<u8> = bseq_as!(NonZero<u8>; 0000 0001);
+    assert_eq!(ONE.get(), 1);
+}
+
+#[test]
+#[should_panic(expected = "the bit sequence is zero")]
+fn test_bseq_as_non_zero_zero() {
+    let x = std::hint::black_box(0);
+    bseq_as!(std::num::NonZeroU8; 0000 x:4);
+}
+
+#[test]
+fn test_bseq_evaluation_order() {
+    let mut fifo = [0xa, 0xb, 0xc].into_iter();
+    assert_eq!(bseq!(fifo.next().unwrap():4 fifo.next().unwrap():4 fifo.next().unwrap():4), 0xabc);
+
+    let mut calls = Vec::new();
+    let mut read = |value: u32| {
+        calls.push(value);
+        value
+    };
+    let t = bseq_16!(read(1):4 !read(2):4 0xf (read(3) + 1):4);
+    assert_eq!(t, 0x1d_f4);
+    assert_eq!(calls, [1, 2, 3]);
+
+    // strict mode and runtime lengths evaluate each expression exactly once as well
+    let mut calls = Vec::new();
+    let mut read = |value: usize| {
+        calls.push(value);
+        value
+    };
+    let t = bseq!(@strict; read(1):(read(2)) read(3):(read(2)));
+    assert_eq!(t, 0b01_11);
+    assert_eq!(calls, [1, 2, 3, 2]);
+
+    // segments are evaluated in the order they are written, even if `@lsb;` lays them out in reverse
+    let mut fifo = [0xa, 0xb].into_iter();
+    assert_eq!(bseq!(@lsb; fifo.next().unwrap():4 fifo.next().unwrap():4), 0xba);
+
+    // the temporaries do not capture variables of the caller with the same name
+    let x = 0b11;
+    let __bseq_value_0 = 0b101;
+    assert_eq!(bseq!(x:2 __bseq_value_0:3), 0b11_101);
+}
+
+#[repr(u8)]
+enum Opcode {
+    Load = bseq_8!(0000 011),
+    Store = bseq_8!(0x2:4 011),
+}
+
+#[test]
+fn test_bseq_constant_folding() {
+    // sequences of constant segments expand to a single literal
+    let kind = match 0b0110_01_0_1 {
+        bseq!(0110 01 0 1) => "folded",
+        _ => "not folded",
+    };
+    assert_eq!(kind, "folded");
+    let kind = match 0b01_0001_1111 {
+        bseq!(01 0x1f) => "hex",
+        bseq!(1 !0:4) => "ones",
+        bseq!(3:2 0o6) => "octal",
+        _ => "not folded",
+    };
+    assert_eq!(kind, "hex");
+    assert!(matches!(0b1_1111, bseq!(1 !0:4)));
+    assert!(matches!(0b11_110, bseq!(3:2 0o6)));
+    assert_eq!([0u8; bseq!(1 0 0)].len(), 4);
+    assert_eq!([0u8; bseq!(0x1:2 0)].len(), 2);
+    // zeros above all set bits are not folded, as their masks check their widths
+    let zeros: u16 = bseq!(0:9);
+    assert_eq!(zeros, 0);
+    let zeros: u16 = bseq!(0x0:4 01 0:3);
+    assert_eq!(zeros, 0b01_000);
+    assert_eq!(Opcode::Load as u8, 0b0000_011);
+    assert_eq!(Opcode::Store as u8, 0b0010_011);
+    const WORD: u32 = bseq_32!(0xab 3:2 !0:6 -1:4 ..0);
+    assert_eq!(WORD, 0xab_fff << 12);
+    assert_eq!(bseq_16!(4'hA 0xffu8:8 1{4}), 0xa_ff_f);
+    assert_eq!(bseq_16!(!0u8:12 !(1u8):4), 0x0ff_e);
+
+    // constant runs within mixed sequences are folded as well
+    let rd = 0b00101;
+    assert_eq!(bseq_32!(0110 1:1 rd:5 0x3 !0:2 rd:(3)), 0b0110_1_00101_0011_11_101);
+    assert_eq!(bseq!(01 rd:5 10 0:3), 0b01_00101_10_000);
+    assert_eq!(bseq_16_left!(101 rd:5 11), 0b101_00101_11 << 6);
+}
+
+struct Csr<const ADDR: u16>;
+
+impl<const ADDR: u16> Csr<ADDR> {
+    fn addr(&self) -> u16 {
+        ADDR
+    }
+}
+
+fn classify(op: u8) -> &'static str {
+    match op {
+        bseq_lit!(0110 01) => "load",
+        bseq_lit!(0x1:4 1{4}) => "store",
+        bseq_lit!(0000 ..= 0111) => "alu",
+        bseq_lit!(@lsb; 0 0 0 1 ..= 1 0 1 1) => "branch",
+        bseq_lit!(@8; 0001 0000 ..= 0001 1110) => "system",
+        _ => "unknown",
+    }
+}
+
+// the braces around a macro in a generic argument are required, as it would be parsed as a type otherwise
+#[allow(unused_braces)]
+#[test]
+fn test_bseq_lit() {
+    assert_eq!(classify(0b0110_01), "load");
+    assert_eq!(classify(0x1f), "store");
+    assert_eq!(classify(0b0101), "alu");
+    assert_eq!(classify(0b0111), "alu");
+    assert_eq!(classify(0b1010), "branch");
+    assert_eq!(classify(0b1101), "branch");
+    assert_eq!(classify(0b1110), "unknown");
+    assert_eq!(classify(0x1e), "system");
+    assert_eq!(classify(0xff), "unknown");
+
+    assert_eq!(bseq_lit!(0xab !0:4 -1:4 4'o7 ..0 0u32), 0xab_ff_7 << 12);
+    assert_eq!(bseq_lit!(@8; 0x2:4 1010), 0x2a);
+    assert_eq!(Csr::<{ bseq_lit!(0x3 0x00) }>.addr(), 0x300);
+
+    const MASK: u64 = bseq_lit!(1 0:63);
+    assert_eq!(MASK, 1 << 63);
+}
@@ -0,0 +1,10 @@
+use bit_seq::BitEnum;
+
+#[derive(BitEnum)]
+#[bits(2)]
+enum Op {
+    Load = 1,
+    Jump = 4,
+}
+
+fn main() {}
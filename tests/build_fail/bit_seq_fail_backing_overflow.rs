@@ -0,0 +1,11 @@
+use bit_seq::bit_seq;
+
+#[bit_seq(u8)]
+struct Ctrl {
+    #[bits(3)]
+    mode: u8,
+    #[bits(6)]
+    count: u8,
+}
+
+fn main() {}
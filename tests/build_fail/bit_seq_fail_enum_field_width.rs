@@ -0,0 +1,18 @@
+use bit_seq::{bit_seq, BitEnum};
+
+#[derive(BitEnum, Clone, Copy)]
+#[bits(2)]
+enum Mode {
+    Slow,
+    Fast = 3,
+}
+
+#[bit_seq]
+struct Ctrl {
+    #[bits(1)]
+    mode: Mode,
+    #[bits(7)]
+    count: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use bit_seq::bit_seq;
+
+#[bit_seq]
+struct Ctrl {
+    #[bits(4)]
+    mode: u8,
+    #[bits(12)]
+    count: u8,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bit_seq::bit_seq;
+
+#[bit_seq]
+struct Ctrl {
+    #[bits(3)]
+    mode: u8,
+    en: bool,
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+use bit_seq::bseq_as;
+
+fn main() {
+    let _ = bseq_as!(0110 1);
+}
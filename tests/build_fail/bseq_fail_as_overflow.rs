@@ -0,0 +1,6 @@
+use bit_seq::bseq_as;
+
+fn main() {
+    let x = 0;
+    let _ = bseq_as!(i16; 0xff x:8 1);
+}
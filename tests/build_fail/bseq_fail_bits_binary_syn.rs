@@ -1,3 +1,3 @@
 use bit_seq::bseq;
 
-fn main() { bseq!(0b101) }
\ No newline at end of file
+fn main() { bseq!(0110_2) }
@@ -0,0 +1,5 @@
+use bit_seq::bseq_128;
+
+fn main() {
+    let _ = bseq_128!(111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111);
+}
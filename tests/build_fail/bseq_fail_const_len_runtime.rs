@@ -0,0 +1,7 @@
+use bit_seq::bseq;
+
+fn main() {
+    let x = 3;
+    let len = 2;
+    let _ = bseq!(x:{len});
+}
@@ -0,0 +1,6 @@
+use bit_seq::bseq;
+
+fn main() {
+    let (rd, imm) = (1, 2);
+    let _ = bseq!(@32; 0110 rd:5 imm:20);
+}
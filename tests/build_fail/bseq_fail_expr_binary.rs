@@ -0,0 +1,6 @@
+use bit_seq::bseq;
+
+fn main() {
+    let (a, b) = (1, 2);
+    let _ = bseq!(a + b:4);
+}
@@ -0,0 +1,10 @@
+use bit_seq::bseq;
+
+struct Config {
+    flags: u8,
+}
+
+fn main() {
+    let cfg = Config { flags: 1 };
+    let _ = bseq!(1 cfg.flags.:3);
+}
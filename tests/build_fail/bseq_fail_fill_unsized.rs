@@ -0,0 +1,5 @@
+use bit_seq::bseq;
+
+fn main() {
+    let _ = bseq!(1101 ..0);
+}
@@ -0,0 +1,6 @@
+use bit_seq::bseq_16_left;
+
+fn main() {
+    let x = 0;
+    let _ = bseq_16_left!(0xff x:8 1);
+}
@@ -0,0 +1,6 @@
+use bit_seq::bseq;
+
+fn main() {
+    let x = 1u128;
+    let _ = bseq!(x:129);
+}
@@ -0,0 +1,5 @@
+use bit_seq::bseq_lit;
+
+fn main() {
+    let _: u8 = bseq_lit!(1 0:8);
+}
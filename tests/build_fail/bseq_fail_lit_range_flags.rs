@@ -0,0 +1,5 @@
+use bit_seq::bseq_lit;
+
+fn main() {
+    let _ = bseq_lit!(0000 ..= @lsb; 0111);
+}
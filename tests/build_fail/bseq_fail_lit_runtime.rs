@@ -0,0 +1,6 @@
+use bit_seq::bseq_lit;
+
+fn main() {
+    let rd = 5;
+    let _ = bseq_lit!(0110 rd:5);
+}
@@ -0,0 +1,9 @@
+use bit_seq::bseq;
+
+mod isa {
+    pub const OP_LOAD: u32 = 3;
+}
+
+fn main() {
+    let _ = bseq!(isa::OP_LOAD::7);
+}
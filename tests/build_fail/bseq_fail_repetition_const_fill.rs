@@ -0,0 +1,7 @@
+use bit_seq::bseq_8;
+
+const N: usize = 4;
+
+fn main() {
+    let _ = bseq_8!([1 ..0]{N});
+}
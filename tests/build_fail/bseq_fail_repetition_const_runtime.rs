@@ -0,0 +1,8 @@
+use bit_seq::bseq;
+
+const N: usize = 4;
+
+fn main() {
+    let (x, len) = (1, 2);
+    let _ = bseq!([1 x:(len)]{N});
+}
@@ -0,0 +1,5 @@
+use bit_seq::bseq_i16;
+
+fn main() {
+    let _ = bseq_i16!(@strict; 2048:s12);
+}
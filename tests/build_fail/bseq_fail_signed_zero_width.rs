@@ -0,0 +1,6 @@
+use bit_seq::bseq_i8;
+
+fn main() {
+    let imm = -1;
+    let _ = bseq_i8!(imm:s0);
+}
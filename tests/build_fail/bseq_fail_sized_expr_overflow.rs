@@ -0,0 +1,6 @@
+use bit_seq::bseq_16;
+
+fn main() {
+    let (op, imm) = (3, 4);
+    let _ = bseq_16!(1 op:4 imm:12 0);
+}
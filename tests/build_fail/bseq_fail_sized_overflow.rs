@@ -0,0 +1,5 @@
+use bit_seq::bseq_8;
+
+fn main() {
+    let _ = bseq_8!(0xff 1);
+}
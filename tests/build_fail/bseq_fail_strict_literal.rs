@@ -0,0 +1,5 @@
+use bit_seq::bseq_strict;
+
+fn main() {
+    let _ = bseq_strict!(1 0xf:3 0);
+}
@@ -0,0 +1,9 @@
+use bit_seq::bseq_match;
+
+fn main() {
+    let word: u8 = 0;
+    let _ = bseq_match!(word {
+        01 rd:6, 0
+        _ => 0,
+    });
+}
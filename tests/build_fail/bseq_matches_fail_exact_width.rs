@@ -0,0 +1,6 @@
+use bit_seq::bseq_matches;
+
+fn main() {
+    let word: u8 = 0b0110;
+    let _ = bseq_matches!(word, @8; 0110);
+}
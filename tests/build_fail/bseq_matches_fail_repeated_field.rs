@@ -0,0 +1,8 @@
+use bit_seq::bseq_matches;
+
+const N: usize = 2;
+
+fn main() {
+    let word: u8 = 0b0110_0110;
+    let _ = bseq_matches!(word, [01 x:2]{N});
+}
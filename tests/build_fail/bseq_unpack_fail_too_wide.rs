@@ -0,0 +1,7 @@
+use bit_seq::bseq_unpack;
+
+const LOW: u8 = bseq_unpack!(0x12u8 => 0x1 low:8);
+
+fn main() {
+    let _ = LOW;
+}